@@ -0,0 +1,20 @@
+// generates the C header for the ffi feature into OUT_DIR, the checked in include/rs_floppoker.h is updated by hand
+// so builds never write to the source tree
+fn main() {
+    println!("cargo:rerun-if-changed=build.rs");
+    #[cfg(feature = "ffi")]
+    {
+        println!("cargo:rerun-if-changed=src/ffi.rs");
+        println!("cargo:rerun-if-changed=cbindgen.toml");
+        let crate_dir = std::env::var("CARGO_MANIFEST_DIR").unwrap();
+        let out_dir = std::env::var("OUT_DIR").unwrap();
+        let config = cbindgen::Config::from_file(format!("{}/cbindgen.toml", crate_dir))
+            .expect("cbindgen.toml not found");
+        cbindgen::Builder::new()
+            .with_crate(&crate_dir)
+            .with_config(config)
+            .generate()
+            .expect("Unable to generate C header")
+            .write_to_file(format!("{}/rs_floppoker.h", out_dir));
+    }
+}
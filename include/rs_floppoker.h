@@ -0,0 +1,136 @@
+#ifndef RS_FLOPPOKER_H
+#define RS_FLOPPOKER_H
+
+/* Generated by cbindgen from src/ffi.rs with the ffi feature, do not edit */
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+typedef enum FpStatus {
+  FP_STATUS_OK = 0,
+  FP_STATUS_NULL_POINTER = 1,
+  FP_STATUS_NOT_YOUR_TURN = 2,
+  FP_STATUS_HAND_OVER = 3,
+  FP_STATUS_HAND_NOT_OVER = 4,
+  FP_STATUS_ILLEGAL_ACTION = 5,
+  FP_STATUS_INVALID_VALUE = 6,
+  FP_STATUS_SEAT_NOT_FOUND = 7,
+  FP_STATUS_NOT_ENOUGH_CHIPS = 8,
+  FP_STATUS_NOT_ENOUGH_PLAYERS = 9,
+  FP_STATUS_BUFFER_TOO_SMALL = 10,
+  FP_STATUS_NO_HAND = 11,
+  FP_STATUS_NOT_SETTLED = 12,
+  FP_STATUS_ALREADY_SETTLED = 13,
+  FP_STATUS_SEAT_TAKEN = 14,
+  FP_STATUS_BUY_IN_OUT_OF_RANGE = 15,
+  FP_STATUS_UNKNOWN_ACTION_TYPE = 16,
+  FP_STATUS_INVALID_CONFIG = 17,
+  FP_STATUS_PANIC = 99,
+} FpStatus;
+
+typedef enum FpActionType {
+  FP_ACTION_TYPE_FOLD = 0,
+  FP_ACTION_TYPE_CHECK = 1,
+  FP_ACTION_TYPE_CALL = 2,
+  FP_ACTION_TYPE_CALL_AI = 3,
+  FP_ACTION_TYPE_BET = 4,
+  FP_ACTION_TYPE_BET_AI = 5,
+  FP_ACTION_TYPE_RAISE = 6,
+  FP_ACTION_TYPE_RAISE_AI = 7,
+} FpActionType;
+
+typedef enum FpStage {
+  FP_STAGE_WAITING_FOR_HAND = 0,
+  FP_STAGE_PRE_FLOP = 1,
+  FP_STAGE_FLOP = 2,
+  FP_STAGE_AWAITING_SETTLEMENT = 3,
+  FP_STAGE_SETTLED = 4,
+} FpStage;
+
+typedef struct FpDealer FpDealer;
+
+typedef struct RevealPolicy RevealPolicy;
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+/**
+ * Creates a dealer for num_players players, seats and chips are arrays of num_players.
+ * Returns null if the arguments are invalid. Free the handle with fp_dealer_free.
+ *
+ * # Safety
+ * seats and chips must point to at least num_players values.
+ */
+struct FpDealer *fp_dealer_new(uint64_t seed,
+                               const uint8_t *seats,
+                               const uint16_t *chips,
+                               uintptr_t num_players);
+
+/**
+ * Frees a dealer from fp_dealer_new, null is ignored.
+ *
+ * # Safety
+ * handle must come from fp_dealer_new and not be used after this call.
+ */
+void fp_dealer_free(struct FpDealer *handle);
+
+/**
+ * Deals a new hand and posts the blinds.
+ *
+ * # Safety
+ * handle must be null or come from fp_dealer_new.
+ */
+enum FpStatus fp_new_hand(struct FpDealer *handle);
+
+/**
+ * Applies an action for seat, t is a FpActionType value, value is 0 for fold, check and calls.
+ * Returns FP_STATUS_UNKNOWN_ACTION_TYPE if t is not a FpActionType.
+ *
+ * # Safety
+ * handle must be null or come from fp_dealer_new.
+ */
+enum FpStatus fp_act(struct FpDealer *handle, uint8_t seat, uint8_t t, uint16_t value);
+
+/**
+ * Writes the current player's available actions into out and the count into out_len.
+ *
+ * # Safety
+ * handle must be null or come from fp_dealer_new, out must have room for cap values.
+ */
+enum FpStatus fp_legal_actions(struct FpDealer *handle,
+                               enum FpActionType *out,
+                               uintptr_t cap,
+                               uintptr_t *out_len);
+
+/**
+ * Pays out the pot once the hand has reached showdown.
+ *
+ * # Safety
+ * handle must be null or come from fp_dealer_new.
+ */
+enum FpStatus fp_showdown(struct FpDealer *handle);
+
+/**
+ * Writes the stage, the seat to act and the pot.
+ *
+ * # Safety
+ * handle must be null or come from fp_dealer_new, the out pointers must be valid.
+ */
+enum FpStatus fp_state(struct FpDealer *handle, enum FpStage *stage, uint8_t *curr, uint16_t *pot);
+
+/**
+ * Writes a seat's chips and the amount it has to call.
+ *
+ * # Safety
+ * handle must be null or come from fp_dealer_new, the out pointers must be valid.
+ */
+enum FpStatus fp_player(struct FpDealer *handle, uint8_t seat, uint16_t *chips, uint16_t *call_amt);
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
+
+#endif  /* RS_FLOPPOKER_H */
@@ -0,0 +1,305 @@
+use std::io::{self, BufRead, BufReader, Write};
+use std::net::{TcpListener, TcpStream};
+use std::time::Duration;
+
+use crate::dealer::*;
+use crate::deck::*;
+use crate::player::*;
+
+// text protocol in the style of the annual computer poker competition dealer
+// one line per message, ending in \r\n
+//
+// server -> client, when it is the client's turn
+//   MATCHSTATE:<position>:<hand number>:<betting>:<cards>
+// client -> server, the same state with the action appended
+//   MATCHSTATE:<position>:<hand number>:<betting>:<cards>:<action>
+// server -> every client when the hand is over, net is the chips won or lost this hand
+//   HANDOVER:<position>:<hand number>:<betting>:<cards>:<net>
+//
+// position is the index in the hand's starting order, 0 posts the small blind and the button is last
+// heads up the button is 0 and posts the small blind
+// betting is the ActionHistory, actions are ActionType codes with the value when it is not 0, eg B1,R1,C/CK,B3
+// preflop and flop are split by /
+// cards are the hole cards by position split by |, only your own until the hand is over, then the flop after /
+//   |AsKhQdJc|/2c5d9h
+// actions are written the same way as in betting, eg R5 or CK
+// stacks are reset every hand so nobody busts, illegal or late actions are replaced by a check or fold
+
+pub const ACPC_MATCHSTATE: &str = "MATCHSTATE";
+pub const ACPC_HANDOVER: &str = "HANDOVER";
+
+pub fn format_acpc_action(action: &Action) -> String {
+    if action.value == 0 {
+        action.t.to_string()
+    } else {
+        format!("{}{}", action.t, action.value)
+    }
+}
+
+// parses an action like R5 or CK for the given seat
+pub fn parse_acpc_action(s: &str, seat: u8) -> Result<Action, String> {
+    let s = s.trim();
+    let split = s.find(|c: char| c.is_ascii_digit()).unwrap_or(s.len());
+    let t = s[..split].parse::<ActionType>()?;
+    let value = if split == s.len() {
+        0
+    } else {
+        s[split..].parse::<u16>().map_err(|e| format!("bad action value {:?} {}", s, e))?
+    };
+    Ok(Action { seat, t, value })
+}
+
+fn format_betting(dealer: &Dealer) -> String {
+    let street = |indexes: &Vec<usize>| -> String {
+        indexes.iter()
+            .map(|i| format_acpc_action(&dealer.ah.actions[*i]))
+            .collect::<Vec<String>>()
+            .join(",")
+    };
+    if dealer.ah.f.is_empty() && dealer.flop[0].value == 0 {
+        street(&dealer.ah.pf)
+    } else {
+        format!("{}/{}", street(&dealer.ah.pf), street(&dealer.ah.f))
+    }
+}
+
+// position of a seat in the hand's starting order
+pub fn acpc_position(dealer: &Dealer, seat: u8) -> Option<usize> {
+    dealer.ah.start_bal.iter().position(|sb| sb.seat == seat)
+}
+
+// match state as seen from position, reveal shows every player that did not fold
+pub fn acpc_match_state(dealer: &Dealer, position: usize, hand_number: u32, reveal: bool) -> String {
+    let holes = dealer.ah.start_bal.iter().enumerate().map(|(i, sb)| {
+        let p = dealer.p.iter().find(|p| p.seat == sb.seat).unwrap();
+        if i == position || (reveal && !p.is_folded) {
+            p.hand.iter().map(card_to_string).collect::<String>()
+        } else {
+            String::new()
+        }
+    }).collect::<Vec<String>>().join("|");
+    let mut cards = holes;
+    if dealer.flop[0].value != 0 {
+        cards.push('/');
+        cards.push_str(&dealer.flop.iter().map(card_to_string).collect::<String>());
+    }
+    format!("{}:{}:{}:{}:{}", ACPC_MATCHSTATE, position, hand_number, format_betting(dealer), cards)
+}
+
+// splits a client reply into the echoed state and the action
+pub fn parse_acpc_reply(line: &str) -> Result<(&str, &str), String> {
+    let line = line.trim_end();
+    match line.rfind(':') {
+        Some(i) if line.starts_with(ACPC_MATCHSTATE) => Ok((&line[..i], &line[i + 1..])),
+        _ => Err(format!("bad reply {:?}", line)),
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct AcpcMatch {
+    pub num_players: usize,
+    pub stack: u16,
+    pub hands: u32,
+    pub seed: u64,
+    pub timeout: Duration,
+}
+
+struct AcpcClient {
+    reader: BufReader<TcpStream>,
+    writer: TcpStream,
+}
+
+impl AcpcClient {
+    fn send(&mut self, line: &str) -> io::Result<()> {
+        self.writer.write_all(line.as_bytes())?;
+        self.writer.write_all(b"\r\n")?;
+        self.writer.flush()
+    }
+
+    // asks for an action, None if the client timed out or sent something unusable
+    fn request(&mut self, state: &str, seat: u8) -> io::Result<Option<Action>> {
+        self.send(state)?;
+        let mut line = String::new();
+        match self.reader.read_line(&mut line) {
+            Ok(0) => return Err(io::Error::new(io::ErrorKind::UnexpectedEof, "client disconnected")),
+            Ok(_) => {}
+            Err(e) if e.kind() == io::ErrorKind::WouldBlock || e.kind() == io::ErrorKind::TimedOut => return Ok(None),
+            Err(e) => return Err(e),
+        }
+        Ok(parse_acpc_reply(&line).ok()
+            .filter(|(echo, _)| *echo == state)
+            .and_then(|(_, action)| parse_acpc_action(action, seat).ok()))
+    }
+}
+
+// accepts num_players connections on listener, seats are given in connection order starting at 1
+// then plays the match and returns the net chips won by each seat
+// a match that cant be dealt is InvalidInput before anyone is accepted
+pub fn serve_acpc_match(listener: &TcpListener, m: &AcpcMatch) -> io::Result<Vec<i32>> {
+    if m.num_players < 2 || m.num_players > u8::MAX as usize {
+        return Err(io::Error::new(io::ErrorKind::InvalidInput, "players must be between 2 and 255"));
+    }
+    if m.stack == 0 {
+        return Err(io::Error::new(io::ErrorKind::InvalidInput, "stack must be more than 0"));
+    }
+    let mut clients: Vec<AcpcClient> = vec![];
+    while clients.len() < m.num_players {
+        let (stream, _) = listener.accept()?;
+        stream.set_read_timeout(Some(m.timeout))?;
+        stream.set_nodelay(true)?;
+        clients.push(AcpcClient { reader: BufReader::new(stream.try_clone()?), writer: stream });
+    }
+
+    let mut totals = vec![0_i32; m.num_players];
+    for hand in 0..m.hands {
+        let p = (1..=m.num_players).map(|s| Player::new(s as u8, m.stack)).collect::<Vec<Player>>();
+        let mut dealer = Dealer::new(m.seed.wrapping_add(hand as u64), p);
+        // the button moves one seat every hand
+        dealer.button = (hand as usize % m.num_players) as u8 + 1;
+        dealer.try_new_hand().map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e.to_string()))?;
+
+        while dealer.stage.is_betting() {
+            let seat = dealer.curr;
+            let position = acpc_position(&dealer, seat).unwrap();
+            let state = acpc_match_state(&dealer, position, hand, false);
+            let action = clients[seat as usize - 1].request(&state, seat)?
+                .filter(|a| dealer.check_action(a).is_ok())
+                .unwrap_or_else(|| {
+                    let t = if dealer.get_available_actions().contains(&ActionType::Check) {
+                        ActionType::Check
+                    } else {
+                        ActionType::Fold
+                    };
+                    Action { seat, t, value: 0 }
+                });
+            dealer.p_action(action);
+        }
+        dealer.handle_showdown();
+
+        let reveal = dealer.p.iter().filter(|p| !p.is_folded).count() > 1;
+        for (i, client) in clients.iter_mut().enumerate() {
+            let seat = i as u8 + 1;
+            let net = dealer.p.iter().find(|p| p.seat == seat).unwrap().chips as i32 - m.stack as i32;
+            totals[i] += net;
+            let position = acpc_position(&dealer, seat).unwrap();
+            let state = acpc_match_state(&dealer, position, hand, reveal);
+            let state = state.replacen(ACPC_MATCHSTATE, ACPC_HANDOVER, 1);
+            client.send(&format!("{}:{}", state, net))?;
+        }
+    }
+    Ok(totals)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::thread;
+
+    #[test]
+    fn test_parse_acpc_action() {
+        let a = parse_acpc_action("R5", 2).unwrap();
+        assert_eq!((a.seat, a.t, a.value), (2, ActionType::Raise, 5));
+        let a = parse_acpc_action("CK", 1).unwrap();
+        assert_eq!((a.t, a.value), (ActionType::Check, 0));
+        let a = parse_acpc_action("RA12", 1).unwrap();
+        assert_eq!((a.t, a.value), (ActionType::RaiseAI, 12));
+        assert!(parse_acpc_action("X1", 1).is_err());
+        assert_eq!(parse_acpc_reply("MATCHSTATE:0:3:B1,R1::R5\r\n").unwrap(), ("MATCHSTATE:0:3:B1,R1:", "R5"));
+        assert!(parse_acpc_reply("hello").is_err());
+    }
+
+    #[test]
+    fn test_acpc_match_state() {
+        let mut dealer = Dealer::new(123, vec![
+            Player::new(1, 15),
+            Player::new(2, 12),
+            Player::new(3, 10),
+        ]);
+        dealer.new_hand();
+        dealer.p_action(Action {
+            seat: 1,
+            t: ActionType::Call,
+            value: 0
+        });
+        let state = acpc_match_state(&dealer, 0, 7, false);
+        let hole = dealer.p.iter().find(|p| p.seat == 2).unwrap().hand.iter().map(card_to_string).collect::<String>();
+        assert_eq!(state, format!("MATCHSTATE:0:7:B1,R1,C:{}||", hole));
+        dealer.p_action(Action {
+            seat: 2,
+            t: ActionType::Call,
+            value: 0
+        });
+        dealer.p_action(Action {
+            seat: 3,
+            t: ActionType::Check,
+            value: 0
+        });
+        let state = acpc_match_state(&dealer, 2, 7, false);
+        let flop = dealer.flop.iter().map(card_to_string).collect::<String>();
+        assert!(state.starts_with("MATCHSTATE:2:7:B1,R1,C,C,CK/:||"));
+        assert!(state.ends_with(&format!("/{}", flop)));
+    }
+
+    // connects and answers every request with action, returns the HANDOVER lines
+    fn client(addr: std::net::SocketAddr, action: &'static str) -> thread::JoinHandle<Vec<String>> {
+        thread::spawn(move || {
+            let stream = TcpStream::connect(addr).unwrap();
+            let mut writer = stream.try_clone().unwrap();
+            let mut hand_overs = vec![];
+            for line in BufReader::new(stream).lines() {
+                let line = line.unwrap();
+                if line.starts_with(ACPC_HANDOVER) {
+                    hand_overs.push(line);
+                } else {
+                    write!(writer, "{}:{}\r\n", line, action).unwrap();
+                }
+            }
+            hand_overs
+        })
+    }
+
+    #[test]
+    fn test_serve_acpc_match() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        let m = AcpcMatch {
+            num_players: 3,
+            stack: 20,
+            hands: 4,
+            // the seed wraps around for the later hands
+            seed: u64::MAX,
+            timeout: Duration::from_secs(5),
+        };
+        let server = thread::spawn(move || serve_acpc_match(&listener, &m).unwrap());
+        // connection order is not guaranteed, so every client plays the same way
+        let clients = (0..3).map(|_| {
+            let handle = client(addr, "C");
+            thread::sleep(Duration::from_millis(20));
+            handle
+        }).collect::<Vec<_>>();
+        let totals = server.join().unwrap();
+        assert_eq!(totals.iter().sum::<i32>(), 0);
+        for handle in clients {
+            let hand_overs = handle.join().unwrap();
+            assert_eq!(hand_overs.len(), 4);
+            assert!(hand_overs[0].starts_with("HANDOVER:"));
+        }
+    }
+
+    #[test]
+    fn test_serve_acpc_match_bad_match() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let mut m = AcpcMatch {
+            num_players: 1,
+            stack: 20,
+            hands: 4,
+            seed: 1,
+            timeout: Duration::from_secs(5),
+        };
+        // returns straight away without waiting for a client
+        assert_eq!(serve_acpc_match(&listener, &m).unwrap_err().kind(), io::ErrorKind::InvalidInput);
+        m.num_players = 2;
+        m.stack = 0;
+        assert_eq!(serve_acpc_match(&listener, &m).unwrap_err().kind(), io::ErrorKind::InvalidInput);
+    }
+}
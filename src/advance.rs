@@ -0,0 +1,174 @@
+use crate::dealer::*;
+use crate::error::*;
+use crate::events::*;
+
+// actions picked before it is the player's turn, the dealer plays them as soon as curr gets to the seat
+// after every action the queue is checked against the call amounts, one that no longer fits is dropped
+// with an AdvanceCancelled event, the player then acts as normal
+//   seat 3 queues Call(2), seat 2 raises to 6  ->  seat 3's Call(2) is cancelled
+// FoldToAnyBet stays for the rest of the hand, the rest are used once
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum AdvanceAction {
+    // check, or fold if there is a bet
+    CheckFold,
+    // check, cancelled by a bet
+    Check,
+    // call exactly this much, cancelled if the call amount changes
+    Call(u16),
+    // check or call whatever it costs, all in if short
+    CallAny,
+    // check when possible and fold to the first bet, every street
+    FoldToAnyBet,
+}
+
+impl Dealer {
+    // queues an advance action for a seat still in the hand that isnt curr, replacing one already queued
+    pub fn queue_advance(&mut self, seat: u8, advance: AdvanceAction) -> Result<(), DealerError> {
+        match self.stage {
+            Stages::WaitingForHand => return Err(DealerError::NoHand),
+            Stages::AwaitingSettlement | Stages::Settled => return Err(DealerError::HandOver),
+            _ => {}
+        }
+        let player = self.p.iter().find(|p| p.seat == seat).ok_or(DealerError::SeatNotFound(seat))?;
+        if player.is_folded || player.is_all_in || seat == self.curr {
+            return Err(DealerError::IllegalAction);
+        }
+        if advance == AdvanceAction::Call(0) {
+            return Err(DealerError::InvalidValue);
+        }
+        if !self.advance_fits(seat, &advance) {
+            return Err(DealerError::IllegalAction);
+        }
+        self.advance.retain(|(s, _)| *s != seat);
+        self.advance.push((seat, advance));
+        Ok(())
+    }
+
+    pub fn cancel_advance(&mut self, seat: u8) {
+        self.advance.retain(|(s, _)| *s != seat);
+    }
+
+    // the advance action queued for the seat, if any
+    pub fn queued_advance(&self, seat: u8) -> Option<&AdvanceAction> {
+        self.advance.iter().find(|(s, _)| *s == seat).map(|(_, a)| a)
+    }
+
+    // still what the player asked for with the bets as they are now
+    fn advance_fits(&self, seat: u8, advance: &AdvanceAction) -> bool {
+        let call_amt = self.get_call_amt(&seat);
+        match advance {
+            AdvanceAction::Check => call_amt == 0,
+            AdvanceAction::Call(amt) => call_amt == *amt,
+            _ => true,
+        }
+    }
+
+    // the action an advance action turns into for curr
+    fn advance_to_action(&self, advance: &AdvanceAction) -> Action {
+        let available = self.get_available_actions();
+        let t = if available.contains(&ActionType::Check) {
+            ActionType::Check
+        } else {
+            match advance {
+                AdvanceAction::CheckFold | AdvanceAction::FoldToAnyBet => ActionType::Fold,
+                _ if available.contains(&ActionType::Call) => ActionType::Call,
+                _ => ActionType::CallAI,
+            }
+        };
+        Action { seat: self.curr, t, value: 0 }
+    }
+
+    // drops the advance actions that no longer fit and plays curr's, over and over while curr has one
+    pub(crate) fn run_advance(&mut self) {
+        loop {
+            if !self.stage.is_betting() {
+                self.advance.clear();
+                return;
+            }
+            let stale = self.advance.iter()
+                .filter(|(seat, a)| !self.advance_fits(*seat, a))
+                .map(|(seat, _)| *seat)
+                .collect::<Vec<u8>>();
+            for seat in stale {
+                self.cancel_advance(seat);
+                self.emit(DealerEvent::AdvanceCancelled { seat });
+            }
+            let Some(advance) = self.queued_advance(self.curr).cloned() else {
+                return;
+            };
+            if advance != AdvanceAction::FoldToAnyBet {
+                self.cancel_advance(self.curr);
+            }
+            let action = self.advance_to_action(&advance);
+            self.act(action, false);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::player::*;
+
+    fn four_handed() -> Dealer {
+        let mut dealer = Dealer::new(123, (1..=4).map(|s| Player::new(s, 50)).collect());
+        dealer.new_hand();
+        dealer
+    }
+
+    #[test]
+    fn test_queue_advance() {
+        let mut dealer = four_handed();
+        // seat 4 is first to act preflop
+        assert_eq!(dealer.curr, 4);
+        assert_eq!(dealer.queue_advance(4, AdvanceAction::CallAny), Err(DealerError::IllegalAction));
+        assert_eq!(dealer.queue_advance(9, AdvanceAction::CallAny), Err(DealerError::SeatNotFound(9)));
+        assert_eq!(dealer.queue_advance(1, AdvanceAction::Check), Err(DealerError::IllegalAction));
+        assert_eq!(dealer.queue_advance(1, AdvanceAction::Call(0)), Err(DealerError::InvalidValue));
+        dealer.queue_advance(1, AdvanceAction::Call(2)).unwrap();
+        dealer.queue_advance(2, AdvanceAction::CheckFold).unwrap();
+        dealer.queue_advance(3, AdvanceAction::CallAny).unwrap();
+        assert_eq!(dealer.queued_advance(2), Some(&AdvanceAction::CheckFold));
+
+        // seat 4 calls and the queue plays out to the flop, the bb checks its option
+        dealer.p_action(Action { seat: 4, t: ActionType::Call, value: 0 });
+        assert_eq!(dealer.stage, Stages::Flop);
+        let actions = dealer.ah.actions.iter().skip(3).map(|a| (a.seat, a.t.clone())).collect::<Vec<_>>();
+        assert_eq!(actions, vec![(1, ActionType::Call), (2, ActionType::Fold), (3, ActionType::Check)]);
+        assert!(dealer.advance.is_empty());
+    }
+
+    #[test]
+    fn test_advance_cancelled() {
+        let mut dealer = four_handed();
+        dealer.queue_advance(1, AdvanceAction::Call(2)).unwrap();
+        dealer.queue_advance(2, AdvanceAction::CallAny).unwrap();
+        dealer.drain_events();
+        // the raise changes what seat 1 has to call
+        dealer.p_action(Action { seat: 4, t: ActionType::Raise, value: 4 });
+        assert_eq!(dealer.curr, 1);
+        assert_eq!(dealer.queued_advance(1), None);
+        assert!(dealer.drain_events().contains(&DealerEvent::AdvanceCancelled { seat: 1 }));
+        dealer.p_action(Action { seat: 1, t: ActionType::Fold, value: 0 });
+        // call any still stands
+        assert_eq!(dealer.ah.actions.last().map(|a| (a.seat, a.t.clone())), Some((2, ActionType::Call)));
+        assert_eq!(dealer.curr, 3);
+    }
+
+    #[test]
+    fn test_fold_to_any_bet() {
+        let mut dealer = four_handed();
+        dealer.queue_advance(3, AdvanceAction::FoldToAnyBet).unwrap();
+        dealer.p_action(Action { seat: 4, t: ActionType::Call, value: 0 });
+        dealer.p_action(Action { seat: 1, t: ActionType::Call, value: 0 });
+        dealer.p_action(Action { seat: 2, t: ActionType::Call, value: 0 });
+        // the bb checks its option and keeps the advance action for the flop
+        assert_eq!(dealer.stage, Stages::Flop);
+        assert_eq!(dealer.queued_advance(3), Some(&AdvanceAction::FoldToAnyBet));
+        assert_eq!(dealer.curr, 2);
+        dealer.p_action(Action { seat: 2, t: ActionType::Bet, value: 2 });
+        assert!(dealer.p.iter().find(|p| p.seat == 3).unwrap().is_folded);
+        assert_eq!(dealer.curr, 4);
+    }
+}
@@ -0,0 +1,52 @@
+use std::env;
+use std::net::TcpListener;
+use std::time::Duration;
+
+use rs_floppoker::*;
+
+// acpc style dealer server, see src/acpc.rs for the protocol
+// usage: acpc_dealer <players> <hands> [stack] [seed] [port]
+// port 0 picks a free port, the port is printed once the server is listening
+
+const USAGE: &str = "usage: acpc_dealer <players> <hands> [stack] [seed] [port]";
+
+fn usage(msg: &str) -> ! {
+    eprintln!("{}\n{}", msg, USAGE);
+    std::process::exit(2);
+}
+
+fn arg<T: std::str::FromStr>(args: &[String], i: usize, default: T) -> T {
+    match args.get(i) {
+        Some(a) => a.parse::<T>().unwrap_or_else(|_| usage(&format!("bad argument {:?}", a))),
+        None => default,
+    }
+}
+
+fn main() -> std::io::Result<()> {
+    let args: Vec<String> = env::args().collect();
+    if args.len() < 3 {
+        usage("missing arguments");
+    }
+    let m = AcpcMatch {
+        num_players: arg(&args, 1, 2),
+        hands: arg(&args, 2, 100),
+        stack: arg(&args, 3, 200),
+        seed: arg(&args, 4, 0),
+        timeout: Duration::from_secs(10),
+    };
+    if m.num_players < 2 || m.num_players > u8::MAX as usize {
+        usage("players must be between 2 and 255");
+    }
+    if m.stack == 0 {
+        usage("stack must be more than 0");
+    }
+    let port: u16 = arg(&args, 5, 0);
+    let listener = TcpListener::bind(("127.0.0.1", port))?;
+    println!("{}", listener.local_addr()?.port());
+
+    let totals = serve_acpc_match(&listener, &m)?;
+    for (i, total) in totals.iter().enumerate() {
+        println!("seat {}: {}", i + 1, total);
+    }
+    Ok(())
+}
@@ -0,0 +1,30 @@
+use std::env;
+use std::sync::Arc;
+
+use rs_floppoker::*;
+
+// websocket server hosting dealer tables, see src/server.rs for the messages
+// usage: ws_server [tables] [players per table] [stack] [port]
+
+fn arg<T: std::str::FromStr>(args: &[String], i: usize, default: T) -> T {
+    match args.get(i) {
+        Some(a) => a.parse::<T>().unwrap_or_else(|_| panic!("bad argument {:?}", a)),
+        None => default,
+    }
+}
+
+#[tokio::main]
+async fn main() -> std::io::Result<()> {
+    let args: Vec<String> = env::args().collect();
+    let num_tables: u32 = arg(&args, 1, 1);
+    let num_players: usize = arg(&args, 2, 6);
+    let stack: u16 = arg(&args, 3, 200);
+    let port: u16 = arg(&args, 4, 9000);
+
+    let tables = (1..=num_tables)
+        .map(|id| Table::new(id, id as u64, &vec![stack; num_players]))
+        .collect::<Vec<Table>>();
+    let listener = tokio::net::TcpListener::bind(("127.0.0.1", port)).await?;
+    println!("listening on {}", listener.local_addr()?);
+    serve(listener, Arc::new(Server::new(tables))).await
+}
@@ -0,0 +1,382 @@
+use crate::dealer::*;
+use crate::events::*;
+
+// blinds for cash tables where players sit out and come back between hands
+// move_button moves the button with the dead button rule, the bb always goes on to the next player dealt in
+// the sb goes to the last bb's seat and the button to the last sb's, so nobody can dodge the bb by sitting out
+// a dead sb is not posted, a dead button sits on an empty seat or on a player sitting out
+//   button 1, sb 2, bb 3, seat 3 sits out  ->  button 2, sb 3 dead, bb 4
+// players sitting out miss the blinds that go past them and post them when they come back, the bb live and the sb dead
+// without move_button the button stays put and the two players after it post the blinds
+
+// the next seat after seat in seats going round the table, seats is sorted
+fn next_seat(seats: &[u8], seat: u8) -> u8 {
+    *seats.iter().find(|s| **s > seat).unwrap_or(&seats[0])
+}
+
+// seat is strictly between from and to going round the table
+fn between(from: u8, seat: u8, to: u8) -> bool {
+    if from < to {
+        seat > from && seat < to
+    } else {
+        seat > from || seat < to
+    }
+}
+
+impl Dealer {
+    // seats that would be dealt into the next hand, by seat number
+    pub fn seats_to_deal(&self) -> Vec<u8> {
+        let mut seats = self.p.iter().filter(|p| p.can_play(&self.config)).map(|p| p.seat).collect::<Vec<u8>>();
+        seats.sort();
+        seats
+    }
+
+    // true when the sb of the hand is dealt in and posted
+    pub fn sb_live(&self) -> bool {
+        self.ah.start_bal.iter().any(|sb| sb.seat == self.sb)
+    }
+
+    // moves the button and blinds on for the next hand, call it between hands
+    // heads up the button is the sb and the other player the bb
+    pub fn move_button(&mut self) {
+        let seats = self.seats_to_deal();
+        if seats.len() < 2 {
+            return;
+        }
+        if self.bb == 0 {
+            // no hand yet, nobody has posted anything
+            self.button = next_seat(&seats, self.button);
+            return;
+        }
+        let bb = next_seat(&seats, self.bb);
+        for p in self.p.iter_mut().filter(|p| !p.can_play(&self.config) && between(self.bb, p.seat, bb)) {
+            p.missed_bb = true;
+        }
+        let (button, sb) = if seats.len() == 2 {
+            let sb = *seats.iter().find(|s| **s != bb).unwrap();
+            (sb, sb)
+        } else {
+            if let Some(p) = self.p.iter_mut().find(|p| p.seat == self.bb && !p.can_play(&self.config)) {
+                p.missed_sb = true;
+            }
+            (self.sb, self.bb)
+        };
+        self.button = button;
+        self.next_blinds = Some((sb, bb));
+    }
+
+    // the blinds move_button set up, or the first two players dealt in after the button
+    // heads up the button posts the sb, a dead button goes to the first player dealt in after it
+    // dealt is the seats dealt in, in hand order
+    pub(crate) fn take_blinds(&mut self, dealt: &[u8]) -> (u8, u8) {
+        let next_blinds = self.next_blinds.take();
+        if dealt.len() == 2 {
+            let mut seats = dealt.to_vec();
+            seats.sort();
+            if !seats.contains(&self.button) {
+                self.button = next_seat(&seats, self.button);
+            }
+            return (self.button, next_seat(&seats, self.button));
+        }
+        match next_blinds {
+            Some((sb, bb)) if dealt.contains(&bb) && sb != bb => (sb, bb),
+            _ => (dealt[0], dealt[1]),
+        }
+    }
+
+    // players coming back post what they missed once the blinds are in
+    // the missed bb goes in live, it pays the blinds like a call so the player can check if nobody raises
+    // a missed sb is dead money on top, and so is the bb of a player coming back in the sb
+    // posting the bb covers everything owed, posting the sb covers a missed sb
+    pub(crate) fn post_missed_blinds(&mut self) {
+        let (sb, bb) = (self.sb, self.bb);
+        let sb_live = self.sb_live();
+        let config = &self.config;
+        let mut owing = vec![];
+        for p in self.p.iter_mut().filter(|p| !p.is_folded) {
+            if p.seat == bb {
+                p.missed_sb = false;
+                p.missed_bb = false;
+                continue;
+            }
+            if p.seat == sb && sb_live {
+                p.missed_sb = false;
+            }
+            let owed = p.owed_blinds(config);
+            if owed > 0 {
+                owing.push((p.seat, p.missed_bb && p.seat != sb, owed));
+                p.missed_sb = false;
+                p.missed_bb = false;
+            }
+        }
+        for (seat, live_bb, owed) in owing {
+            let chips = self.p.iter().find(|p| p.seat == seat).unwrap().chips;
+            // a short bb leaves less to call, the rest of the bb goes in dead
+            let live = if live_bb { self.get_call_amt(&seat).min(self.config.big_blind).min(chips) } else { 0 };
+            if live > 0 {
+                self.pay_all_outstanding_bets(&seat, &live);
+                self.clean_s_bets();
+            }
+            let dead = (owed - live).min(chips - live);
+            let player = self.p.iter_mut().find(|p| p.seat == seat).unwrap();
+            player.remove_chips(&dead);
+            if player.chips == 0 {
+                player.is_all_in = true;
+            }
+            if dead > 0 {
+                self.pot += dead;
+                self.commit_dead(&seat, &dead);
+            }
+            self.emit(DealerEvent::BlindPosted { seat, amt: live + dead });
+        }
+    }
+
+    // every player dealt in posts the ante once the blinds are in, it doesnt count towards the call
+    // a player short of it goes all in on the ante and can only win what it matched
+    //   ante 2, seat 3 has 1  ->  seat 3 all in for 1, it can win 1 from every player
+    // a blind posts the ante out of whatever the blind left it
+    pub(crate) fn post_antes(&mut self) {
+        let ante = self.config.ante;
+        if ante == 0 {
+            return;
+        }
+        let mut posted = vec![];
+        for p in self.p.iter_mut().filter(|p| !p.is_folded) {
+            let amt = ante.min(p.chips);
+            if amt > 0 {
+                p.remove_chips(&amt);
+                if p.chips == 0 {
+                    p.is_all_in = true;
+                }
+                posted.push((p.seat, amt));
+            }
+        }
+        for (seat, amt) in posted {
+            self.pot += amt;
+            self.commit_ante(&seat, &amt);
+            self.emit(DealerEvent::AntePosted { seat, amt });
+        }
+        // players all in on the ante cant call the blinds, if one was to act the action moves on
+        let all_in = self.p.iter().filter(|p| p.is_all_in).map(|p| p.seat).collect::<Vec<u8>>();
+        for s_bet in &mut self.s_bets {
+            s_bet.unpaid.retain(|seat| !all_in.contains(seat));
+        }
+        self.clean_s_bets();
+        if self.stage.is_betting() && all_in.contains(&self.curr) {
+            self.update_stage();
+            self.start_turn();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::*;
+    use crate::player::*;
+
+    fn fold_round(dealer: &mut Dealer) {
+        while dealer.stage.is_betting() {
+            let seat = dealer.curr;
+            dealer.p_action(Action { seat, t: ActionType::Fold, value: 0 });
+        }
+        dealer.handle_showdown();
+    }
+
+    fn player(dealer: &Dealer, seat: u8) -> &Player {
+        dealer.p.iter().find(|p| p.seat == seat).unwrap()
+    }
+
+    #[test]
+    fn test_move_button() {
+        let mut dealer = Dealer::new(123, (1..=4).map(|s| Player::new(s, 50)).collect());
+        dealer.new_hand();
+        assert_eq!((dealer.button, dealer.sb, dealer.bb), (1, 2, 3));
+        fold_round(&mut dealer);
+        dealer.move_button();
+        dealer.new_hand();
+        assert_eq!((dealer.button, dealer.sb, dealer.bb), (2, 3, 4));
+        assert_eq!(dealer.curr, 1);
+    }
+
+    #[test]
+    fn test_dead_small_blind() {
+        let mut dealer = Dealer::new(123, (1..=4).map(|s| Player::new(s, 50)).collect());
+        dealer.new_hand();
+        fold_round(&mut dealer);
+        // the bb sits out, the sb is dead next hand
+        dealer.p.iter_mut().find(|p| p.seat == 3).unwrap().sitting_out = true;
+        dealer.move_button();
+        dealer.new_hand();
+        assert_eq!((dealer.button, dealer.sb, dealer.bb), (2, 3, 4));
+        assert!(!dealer.sb_live());
+        assert!(player(&dealer, 3).missed_sb);
+        assert!(player(&dealer, 3).is_folded);
+        assert_eq!(dealer.pot, BIG_BLIND);
+        assert_eq!(dealer.curr, 1);
+        // the bb still gets its option
+        dealer.p_action(Action { seat: 1, t: ActionType::Call, value: 0 });
+        dealer.p_action(Action { seat: 2, t: ActionType::Call, value: 0 });
+        assert_eq!(dealer.stage, Stages::PreFlop);
+        assert_eq!(dealer.curr, 4);
+        dealer.p_action(Action { seat: 4, t: ActionType::Check, value: 0 });
+        assert_eq!(dealer.stage, Stages::Flop);
+        assert_eq!(dealer.curr, 4);
+        fold_round(&mut dealer);
+
+        // the button moves onto seat 3 that is still sitting out
+        dealer.move_button();
+        dealer.new_hand();
+        assert_eq!((dealer.button, dealer.sb, dealer.bb), (3, 4, 1));
+        assert_eq!(dealer.ah.start_bal.iter().map(|sb| sb.seat).collect::<Vec<u8>>(), vec![4, 1, 2]);
+        assert_eq!(dealer.curr, 2);
+    }
+
+    #[test]
+    fn test_dead_button_and_missed_blinds() {
+        let mut dealer = Dealer::new(123, (1..=5).map(|s| Player::new(s, 50)).collect());
+        dealer.new_hand();
+        fold_round(&mut dealer);
+        // seat 4 would be the next bb, it sits out and misses it
+        dealer.p.iter_mut().find(|p| p.seat == 4).unwrap().sitting_out = true;
+        dealer.move_button();
+        dealer.new_hand();
+        assert_eq!((dealer.button, dealer.sb, dealer.bb), (2, 3, 5));
+        assert!(player(&dealer, 4).missed_bb);
+        fold_round(&mut dealer);
+
+        // seat 2 sits out too, it misses the bb once the bb goes past it
+        dealer.p.iter_mut().find(|p| p.seat == 2).unwrap().sitting_out = true;
+        dealer.move_button();
+        dealer.new_hand();
+        assert_eq!((dealer.button, dealer.sb, dealer.bb), (3, 5, 1));
+        assert!(!player(&dealer, 2).missed_bb);
+        fold_round(&mut dealer);
+        dealer.move_button();
+        dealer.new_hand();
+        assert_eq!((dealer.button, dealer.sb, dealer.bb), (5, 1, 3));
+        assert!(player(&dealer, 2).missed_bb);
+        assert!(player(&dealer, 2).is_folded);
+        fold_round(&mut dealer);
+
+        // seat 4 comes back in the bb, posting it covers the one it missed
+        dealer.p.iter_mut().find(|p| p.seat == 4).unwrap().sitting_out = false;
+        dealer.move_button();
+        dealer.new_hand();
+        assert_eq!((dealer.button, dealer.sb, dealer.bb), (1, 3, 4));
+        assert!(!player(&dealer, 4).missed_bb);
+        assert_eq!(player(&dealer, 4).chips, 50 - BIG_BLIND);
+        assert_eq!(dealer.committed.iter().find(|c| c.seat == 4).unwrap().dead, 0);
+        assert_eq!(dealer.pot, SMALL_BLIND + BIG_BLIND);
+        fold_round(&mut dealer);
+
+        // seat 2 comes back outside the bb and posts it live, so none of it is dead
+        dealer.p.iter_mut().find(|p| p.seat == 2).unwrap().sitting_out = false;
+        dealer.move_button();
+        dealer.new_hand();
+        assert_eq!((dealer.button, dealer.sb, dealer.bb), (3, 4, 5));
+        assert!(dealer.drain_events().contains(&DealerEvent::BlindPosted { seat: 2, amt: BIG_BLIND }));
+        assert_eq!(dealer.committed.iter().find(|c| c.seat == 2).unwrap().dead, 0);
+        assert_eq!(dealer.pot, SMALL_BLIND + BIG_BLIND * 2);
+        assert!(!player(&dealer, 2).missed_bb);
+    }
+
+    #[test]
+    fn test_missed_bb_posted_live() {
+        let mut dealer = Dealer::new(123, (1..=5).map(|s| Player::new(s, 50)).collect());
+        dealer.new_hand();
+        fold_round(&mut dealer);
+        dealer.p.iter_mut().find(|p| p.seat == 4).unwrap().sitting_out = true;
+        dealer.move_button();
+        dealer.new_hand();
+        fold_round(&mut dealer);
+        dealer.move_button();
+        dealer.new_hand();
+        fold_round(&mut dealer);
+        assert!(player(&dealer, 4).missed_bb);
+
+        // seat 4 comes back under the gun, its bb counts towards the call
+        dealer.p.iter_mut().find(|p| p.seat == 4).unwrap().sitting_out = false;
+        let chips = player(&dealer, 4).chips;
+        dealer.move_button();
+        dealer.new_hand();
+        assert_eq!((dealer.button, dealer.sb, dealer.bb), (5, 1, 2));
+        assert_eq!(player(&dealer, 4).chips, chips - BIG_BLIND);
+        assert_eq!(dealer.committed.iter().find(|c| c.seat == 4).unwrap().dead, 0);
+        assert_eq!(dealer.committed.iter().find(|c| c.seat == 4).unwrap().street, BIG_BLIND);
+        dealer.p_action(Action { seat: 3, t: ActionType::Call, value: 0 });
+        assert_eq!(dealer.curr, 4);
+        assert_eq!(dealer.get_call_amt(&4), 0);
+        assert!(dealer.get_available_actions().contains(&ActionType::Check));
+        assert!(!dealer.get_available_actions().contains(&ActionType::Call));
+        dealer.p_action(Action { seat: 4, t: ActionType::Check, value: 0 });
+        dealer.p_action(Action { seat: 5, t: ActionType::Call, value: 0 });
+        dealer.p_action(Action { seat: 1, t: ActionType::Call, value: 0 });
+        assert_eq!(dealer.curr, 2);
+        dealer.p_action(Action { seat: 2, t: ActionType::Check, value: 0 });
+        assert_eq!(dealer.stage, Stages::Flop);
+        assert_eq!(dealer.pot, BIG_BLIND * 5);
+    }
+
+    #[test]
+    fn test_all_in_on_the_ante() {
+        let config = TableConfig { ante: 2, ..TableConfig::default() };
+        let p = vec![Player::new(1, 50), Player::new(2, 50), Player::new(3, 50), Player::new(4, 1)];
+        let mut dealer = Dealer::with_config(123, p, config);
+        dealer.new_hand();
+        assert_eq!((dealer.button, dealer.sb, dealer.bb), (1, 2, 3));
+        assert!(player(&dealer, 4).is_all_in);
+        assert_eq!(player(&dealer, 4).chips, 0);
+        assert!(dealer.drain_events().contains(&DealerEvent::AntePosted { seat: 4, amt: 1 }));
+        assert_eq!(dealer.pot, SMALL_BLIND + BIG_BLIND + 2 * 3 + 1);
+        // seat 4 was first to act, the action skips it
+        assert_eq!(dealer.curr, 1);
+        assert_eq!(dealer.get_call_amt(&1), BIG_BLIND);
+        dealer.p_action(Action { seat: 1, t: ActionType::Call, value: 0 });
+        dealer.p_action(Action { seat: 2, t: ActionType::Call, value: 0 });
+        dealer.p_action(Action { seat: 3, t: ActionType::Check, value: 0 });
+        assert_eq!(dealer.stage, Stages::Flop);
+        // seat 4 can win the 1 it put in from each player
+        let pots = dealer.pot_breakdown().pots;
+        assert_eq!(pots.len(), 2);
+        assert_eq!(pots[0].value, 4);
+        assert!(pots[0].contributors.contains(&4));
+        assert_eq!(pots[1].value, 3 * (BIG_BLIND + 1));
+        assert!(!pots[1].contributors.contains(&4));
+    }
+
+    #[test]
+    fn test_dead_button_heads_up() {
+        let mut dealer = Dealer::new(123, (1..=3).map(|s| Player::new(s, 50)).collect());
+        dealer.new_hand();
+        fold_round(&mut dealer);
+        // the button sits out and stays on its seat
+        dealer.p.iter_mut().find(|p| p.seat == 1).unwrap().sitting_out = true;
+        dealer.new_hand();
+        assert_eq!((dealer.button, dealer.sb, dealer.bb), (2, 2, 3));
+        assert_eq!(dealer.curr, 2);
+        dealer.p_action(Action { seat: 2, t: ActionType::Call, value: 0 });
+        dealer.p_action(Action { seat: 3, t: ActionType::Check, value: 0 });
+        // the bb acts first after the flop
+        assert_eq!(dealer.stage, Stages::Flop);
+        assert_eq!(dealer.curr, 3);
+    }
+
+    #[test]
+    fn test_move_button_heads_up() {
+        let mut dealer = Dealer::new(123, (1..=3).map(|s| Player::new(s, 50)).collect());
+        dealer.new_hand();
+        fold_round(&mut dealer);
+        dealer.p.iter_mut().find(|p| p.seat == 1).unwrap().sitting_out = true;
+        dealer.move_button();
+        dealer.new_hand();
+        // the bb skips seat 1 and goes round to seat 2, heads up the button posts the sb
+        assert_eq!((dealer.button, dealer.sb, dealer.bb), (3, 3, 2));
+        assert!(player(&dealer, 1).missed_bb);
+        assert_eq!(dealer.curr, 3);
+        fold_round(&mut dealer);
+        dealer.move_button();
+        dealer.new_hand();
+        assert_eq!((dealer.button, dealer.sb, dealer.bb), (2, 2, 3));
+    }
+}
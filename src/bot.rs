@@ -0,0 +1,344 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::io::{BufRead, BufReader, Write};
+use std::process::{Child, ChildStdin, Command, Stdio};
+use std::sync::mpsc::{channel, Receiver, RecvTimeoutError};
+use std::thread;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use crate::dealer::*;
+use crate::deck::*;
+use crate::error::*;
+use crate::view::*;
+
+// json lines protocol for bots running as subprocesses, built with the bots feature
+// the engine writes one json object per line to the bot's stdin, the bot only answers "act" messages
+// with one json line on stdout, eg {"t":"R","value":5}, t is an ActionType code
+// anything else on stdout, a late answer, or an illegal action is a fault and the engine checks or folds for the bot
+//
+// engine -> bot
+//   {"type":"act","protocol":1,"seat":1,"hand":["As","Kh","Qd","Jc"],...}   see BotObservation
+//   {"type":"hand_over","protocol":1,"chips":[{"seat":1,"chips":20},...],"shown":[{"seat":2,"hand":["As","Kh","Qd","Jc"]}]}
+// bot -> engine
+//   {"t":"C"} or {"t":"R","value":5}
+
+pub const BOT_PROTOCOL_VERSION: u32 = 1;
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct BotSeat {
+    pub seat: u8,
+    pub chips: u16,
+    pub is_folded: bool,
+    pub is_all_in: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct BotAction {
+    pub seat: u8,
+    pub t: String,
+    pub value: u16,
+}
+
+// hole cards shown at showdown
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct BotShownHand {
+    pub seat: u8,
+    pub hand: Vec<String>,
+}
+
+// what the acting seat gets to see, built from Dealer::view_for so only its own hole cards
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct BotObservation {
+    pub seat: u8,
+    pub stage: String,
+    pub hand: Vec<String>,
+    pub flop: Vec<String>,
+    pub button: u8,
+    pub pot: u16,
+    pub call_amt: u16,
+    pub bet_range: (u16, u16),
+    pub legal: Vec<String>,
+    pub players: Vec<BotSeat>,
+    pub preflop: Vec<BotAction>,
+    pub flop_actions: Vec<BotAction>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum EngineMsg {
+    Act {
+        protocol: u32,
+        #[serde(flatten)]
+        obs: BotObservation,
+    },
+    HandOver {
+        protocol: u32,
+        chips: Vec<BotSeat>,
+        shown: Vec<BotShownHand>,
+    },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct BotReply {
+    pub t: String,
+    #[serde(default)]
+    pub value: u16,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum BotError {
+    Io(String),
+    Timeout,
+    Disconnected,
+    BadReply(String),
+    Illegal(String),
+}
+
+impl fmt::Display for BotError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            BotError::Io(e) => write!(f, "bot io error {}", e),
+            BotError::Timeout => write!(f, "bot timed out"),
+            BotError::Disconnected => write!(f, "bot disconnected"),
+            BotError::BadReply(line) => write!(f, "bad bot reply {:?}", line),
+            BotError::Illegal(e) => write!(f, "illegal bot action {}", e),
+        }
+    }
+}
+
+impl std::error::Error for BotError {}
+
+// a bot fault during play_hand, the engine acted for the seat with `replaced_by`
+#[derive(Debug, Clone, PartialEq)]
+pub struct BotFault {
+    pub seat: u8,
+    pub error: BotError,
+    pub replaced_by: ActionType,
+}
+
+fn bot_actions(view: &TableView, indexes: &[usize]) -> Vec<BotAction> {
+    indexes.iter().map(|i| {
+        let a = &view.actions[*i];
+        BotAction { seat: a.seat, t: a.t.to_string(), value: a.value }
+    }).collect()
+}
+
+fn bot_seats(view: &TableView) -> Vec<BotSeat> {
+    view.players.iter().map(|p| BotSeat {
+        seat: p.seat,
+        chips: p.chips,
+        is_folded: p.is_folded,
+        is_all_in: p.is_all_in,
+    }).collect()
+}
+
+// builds the observation for the current player
+pub fn bot_observation(dealer: &Dealer) -> BotObservation {
+    let seat = dealer.curr;
+    let view = dealer.view_for(seat, &RevealPolicy::Never);
+    BotObservation {
+        seat,
+        stage: format!("{:?}", view.stage),
+        hand: view.player(seat).and_then(|p| p.hand).map(|h| h.iter().map(card_to_string).collect()).unwrap_or_default(),
+        flop: view.flop.map(|f| f.iter().map(card_to_string).collect()).unwrap_or_default(),
+        button: view.button,
+        pot: view.pot,
+        call_amt: view.call_amt,
+        bet_range: dealer.get_bet_range(),
+        legal: view.legal.iter().map(|t| t.to_string()).collect(),
+        players: bot_seats(&view),
+        preflop: bot_actions(&view, &view.pf),
+        flop_actions: bot_actions(&view, &view.f),
+    }
+}
+
+pub struct BotProcess {
+    child: Child,
+    stdin: ChildStdin,
+    lines: Receiver<String>,
+}
+
+impl BotProcess {
+    pub fn spawn(program: &str, args: &[&str]) -> Result<Self, BotError> {
+        let mut child = Command::new(program)
+            .args(args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::inherit())
+            .spawn()
+            .map_err(|e| BotError::Io(e.to_string()))?;
+        let stdin = child.stdin.take().unwrap();
+        let stdout = child.stdout.take().unwrap();
+        // reading on a thread so request_action can time out
+        let (tx, rx) = channel();
+        thread::spawn(move || {
+            for line in BufReader::new(stdout).lines() {
+                match line {
+                    Ok(line) => {
+                        if tx.send(line).is_err() {
+                            break;
+                        }
+                    }
+                    Err(_) => break,
+                }
+            }
+        });
+        Ok(BotProcess { child, stdin, lines: rx })
+    }
+
+    pub fn send(&mut self, msg: &EngineMsg) -> Result<(), BotError> {
+        let line = serde_json::to_string(msg).map_err(|e| BotError::Io(e.to_string()))?;
+        writeln!(self.stdin, "{}", line).map_err(|e| BotError::Io(e.to_string()))?;
+        self.stdin.flush().map_err(|e| BotError::Io(e.to_string()))
+    }
+
+    // sends an act message and waits up to timeout for the reply
+    // the reply is parsed but not checked against the rules
+    pub fn request_action(&mut self, obs: &BotObservation, timeout: Duration) -> Result<Action, BotError> {
+        // drop anything the bot sent late for an earlier request
+        while self.lines.try_recv().is_ok() {}
+        self.send(&EngineMsg::Act { protocol: BOT_PROTOCOL_VERSION, obs: obs.clone() })?;
+        let line = match self.lines.recv_timeout(timeout) {
+            Ok(line) => line,
+            Err(RecvTimeoutError::Timeout) => return Err(BotError::Timeout),
+            Err(RecvTimeoutError::Disconnected) => return Err(BotError::Disconnected),
+        };
+        let reply: BotReply = serde_json::from_str(&line).map_err(|_| BotError::BadReply(line.clone()))?;
+        let t = reply.t.parse::<ActionType>().map_err(|_| BotError::BadReply(line.clone()))?;
+        Ok(Action { seat: obs.seat, t, value: reply.value })
+    }
+}
+
+impl Drop for BotProcess {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+// plays one hand from new_hand to showdown, asking the bot at each seat for its actions
+// faults are returned and the engine checks, or folds if it cant check, for the faulty bot
+// every seat dealt in needs a bot, a missing one is SeatNotFound before any cards are dealt
+pub fn play_hand(dealer: &mut Dealer, bots: &mut HashMap<u8, BotProcess>, timeout: Duration) -> Result<Vec<BotFault>, DealerError> {
+    let mut faults: Vec<BotFault> = vec![];
+    if let Some(seat) = dealer.seats_to_deal().into_iter().find(|s| !bots.contains_key(s)) {
+        return Err(DealerError::SeatNotFound(seat));
+    }
+    dealer.try_new_hand()?;
+    while dealer.stage.is_betting() {
+        let seat = dealer.curr;
+        let obs = bot_observation(dealer);
+        let bot = bots.get_mut(&seat).ok_or(DealerError::SeatNotFound(seat))?;
+        let result = bot.request_action(&obs, timeout).and_then(|action| {
+            dealer.check_action(&action).map_err(|e| BotError::Illegal(e.to_string()))?;
+            Ok(action)
+        });
+        let action = match result {
+            Ok(action) => action,
+            Err(error) => {
+                let t = if dealer.get_available_actions().contains(&ActionType::Check) {
+                    ActionType::Check
+                } else {
+                    ActionType::Fold
+                };
+                faults.push(BotFault { seat, error, replaced_by: t.clone() });
+                Action { seat, t, value: 0 }
+            }
+        };
+        dealer.p_action(action);
+    }
+    dealer.try_handle_showdown()?;
+    let msg = EngineMsg::HandOver {
+        protocol: BOT_PROTOCOL_VERSION,
+        chips: bot_seats(&dealer.spectator_view()),
+        shown: dealer.shown_hands(&RevealPolicy::Showdown).iter()
+            .map(|(seat, hand)| BotShownHand { seat: *seat, hand: hand.iter().map(card_to_string).collect() })
+            .collect(),
+    };
+    for bot in bots.values_mut() {
+        // a bot that already quit will show up as a fault next hand
+        let _ = bot.send(&msg);
+    }
+    Ok(faults)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::player::*;
+
+    // answers every act message with reply
+    fn sh_bot(reply: &str) -> BotProcess {
+        let script = format!("while read l; do case \"$l\" in *'\"type\":\"act\"'*) echo '{}';; esac; done", reply);
+        BotProcess::spawn("sh", &["-c", &script]).unwrap()
+    }
+
+    fn new_dealer() -> Dealer {
+        Dealer::new(123, vec![
+            Player::new(1, 15),
+            Player::new(2, 12),
+            Player::new(3, 10),
+        ])
+    }
+
+    #[test]
+    fn test_observation_json() {
+        let mut dealer = new_dealer();
+        dealer.new_hand();
+        let obs = bot_observation(&dealer);
+        assert_eq!(obs.seat, 1);
+        assert_eq!(obs.legal, vec!["F", "C", "R"]);
+        assert_eq!(obs.hand.len(), 4);
+        assert!(obs.flop.is_empty());
+        let line = serde_json::to_string(&EngineMsg::Act { protocol: BOT_PROTOCOL_VERSION, obs: obs.clone() }).unwrap();
+        assert!(line.starts_with("{\"type\":\"act\",\"protocol\":1,\"seat\":1,"));
+        let back: EngineMsg = serde_json::from_str(&line).unwrap();
+        assert_eq!(back, EngineMsg::Act { protocol: BOT_PROTOCOL_VERSION, obs });
+    }
+
+    #[test]
+    fn test_play_hand_folding_bots() {
+        let mut dealer = new_dealer();
+        let mut bots: HashMap<u8, BotProcess> = HashMap::new();
+        for seat in 1..=3 {
+            bots.insert(seat, sh_bot("{\"t\":\"F\"}"));
+        }
+        let faults = play_hand(&mut dealer, &mut bots, Duration::from_secs(5)).unwrap();
+        assert!(faults.is_empty());
+        // seat 1 and the sb fold, bb takes the blinds
+        assert_eq!(dealer.p.iter().find(|p| p.seat == 3).unwrap().chips, 11);
+    }
+
+    #[test]
+    fn test_play_hand_faults() {
+        let mut dealer = new_dealer();
+        let mut bots: HashMap<u8, BotProcess> = HashMap::new();
+        bots.insert(1, BotProcess::spawn("sh", &["-c", "cat > /dev/null"]).unwrap());
+        bots.insert(2, sh_bot("{\"t\":\"R\",\"value\":99}"));
+        bots.insert(3, sh_bot("not json"));
+        let faults = play_hand(&mut dealer, &mut bots, Duration::from_millis(200)).unwrap();
+        assert_eq!(faults[0], BotFault { seat: 1, error: BotError::Timeout, replaced_by: ActionType::Fold });
+        assert_eq!(faults[1].seat, 2);
+        assert!(matches!(faults[1].error, BotError::Illegal(_)));
+        assert_eq!(faults[1].replaced_by, ActionType::Fold);
+        assert_eq!(dealer.stage, Stages::Settled);
+        assert_eq!(dealer.pot, 0);
+    }
+
+    #[test]
+    fn test_play_hand_missing_bot() {
+        let mut dealer = new_dealer();
+        let mut bots: HashMap<u8, BotProcess> = HashMap::new();
+        bots.insert(1, sh_bot("{\"t\":\"F\"}"));
+        bots.insert(3, sh_bot("{\"t\":\"F\"}"));
+        assert_eq!(play_hand(&mut dealer, &mut bots, Duration::from_secs(5)), Err(DealerError::SeatNotFound(2)));
+        // nothing was dealt
+        assert_eq!(dealer.stage, Stages::WaitingForHand);
+        assert_eq!(dealer.pot, 0);
+
+        dealer.p.retain(|p| p.seat == 1);
+        assert_eq!(play_hand(&mut dealer, &mut bots, Duration::from_secs(5)), Err(DealerError::NotEnoughPlayers));
+    }
+}
@@ -0,0 +1,159 @@
+use std::fmt;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::OnceLock;
+use std::time::Instant;
+use crate::dealer::*;
+use crate::events::*;
+
+// action clock, off unless TableConfig.action_time is set
+// curr gets action_time to act, then its time bank, after that poll_clock checks for it or folds if it cant check
+// time over action_time comes out of the bank when the player acts, the bank doesnt refill
+//   action_time 15000, bank 30000, acts after 20000  ->  bank 25000
+// the dealer never reads the time on its own, call poll_clock every so often to enforce the deadline
+
+// milliseconds from any fixed point, only the differences are used
+pub trait Clock: fmt::Debug + Send + Sync {
+    fn now(&self) -> u64;
+}
+
+// the real time, the start point is the first call
+#[derive(Debug, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> u64 {
+        static START: OnceLock<Instant> = OnceLock::new();
+        START.get_or_init(Instant::now).elapsed().as_millis() as u64
+    }
+}
+
+// time that only moves when it is told to, for tests and replays
+#[derive(Debug, Default)]
+pub struct FakeClock {
+    ms: AtomicU64,
+}
+
+impl FakeClock {
+    pub fn set(&self, ms: u64) {
+        self.ms.store(ms, Ordering::Relaxed);
+    }
+
+    pub fn advance(&self, ms: u64) {
+        self.ms.fetch_add(ms, Ordering::Relaxed);
+    }
+}
+
+impl Clock for FakeClock {
+    fn now(&self) -> u64 {
+        self.ms.load(Ordering::Relaxed)
+    }
+}
+
+impl Dealer {
+    // when curr runs out of time, its time bank included, None when the clock is off or no one is to act
+    pub fn deadline(&self) -> Option<u64> {
+        let action_time = self.config.action_time?;
+        if !self.stage.is_betting() {
+            return None;
+        }
+        let bank = self.p.iter().find(|p| p.seat == self.curr).map_or(0, |p| p.time_bank);
+        Some(self.turn_started? + action_time + bank)
+    }
+
+    // acts for curr if its time is up, Check when it can or else Fold
+    // returns the action taken for it
+    pub fn poll_clock(&mut self) -> Option<Action> {
+        let deadline = self.deadline()?;
+        if self.clock.now() < deadline {
+            return None;
+        }
+        let seat = self.curr;
+        let t = if self.get_available_actions().contains(&ActionType::Check) {
+            ActionType::Check
+        } else {
+            ActionType::Fold
+        };
+        self.emit(DealerEvent::TimedOut { seat });
+        let action = Action { seat, t, value: 0 };
+        self.p_action(action.clone());
+        Some(action)
+    }
+
+    // takes the time curr went over action_time out of its bank
+    pub(crate) fn charge_time(&mut self) {
+        let (Some(action_time), Some(started)) = (self.config.action_time, self.turn_started) else {
+            return;
+        };
+        let over = self.clock.now().saturating_sub(started).saturating_sub(action_time);
+        if let Some(p) = self.p.iter_mut().find(|p| p.seat == self.curr) {
+            p.time_bank = p.time_bank.saturating_sub(over);
+        }
+    }
+
+    // starts the clock for whoever is to act now
+    pub(crate) fn start_turn(&mut self) {
+        self.turn_started = if self.config.action_time.is_some() && self.stage.is_betting() {
+            Some(self.clock.now())
+        } else {
+            None
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+    use crate::config::*;
+    use crate::player::*;
+
+    fn clocked_dealer(clock: Arc<FakeClock>) -> Dealer {
+        let config = TableConfig { action_time: Some(1000), time_bank: 5000, ..TableConfig::default() };
+        let mut dealer = Dealer::with_config(123, (1..=3).map(|s| Player::new(s, 50)).collect(), config);
+        dealer.clock = clock;
+        dealer
+    }
+
+    #[test]
+    fn test_time_bank() {
+        let clock = Arc::new(FakeClock::default());
+        let mut dealer = clocked_dealer(clock.clone());
+        assert_eq!(dealer.deadline(), None);
+        dealer.new_hand();
+        assert_eq!(dealer.deadline(), Some(6000));
+        clock.advance(3000);
+        assert!(dealer.poll_clock().is_none());
+        dealer.p_action(Action { seat: 1, t: ActionType::Call, value: 0 });
+        // 2000 over the action time came out of the bank
+        assert_eq!(dealer.p.iter().find(|p| p.seat == 1).unwrap().time_bank, 3000);
+        assert_eq!(dealer.deadline(), Some(3000 + 6000));
+        clock.advance(500);
+        dealer.p_action(Action { seat: 2, t: ActionType::Call, value: 0 });
+        assert_eq!(dealer.p.iter().find(|p| p.seat == 2).unwrap().time_bank, 5000);
+    }
+
+    #[test]
+    fn test_timeout_checks_or_folds() {
+        let clock = Arc::new(FakeClock::default());
+        let mut dealer = clocked_dealer(clock.clone());
+        dealer.new_hand();
+        clock.advance(5999);
+        assert!(dealer.poll_clock().is_none());
+        clock.advance(1);
+        // facing the bb, seat 1 cant check
+        let action = dealer.poll_clock().unwrap();
+        assert_eq!((action.seat, action.t), (1, ActionType::Fold));
+        assert!(dealer.p.iter().find(|p| p.seat == 1).unwrap().is_folded);
+        assert_eq!(dealer.p.iter().find(|p| p.seat == 1).unwrap().time_bank, 0);
+        let events = dealer.drain_events();
+        assert!(events.contains(&DealerEvent::TimedOut { seat: 1 }));
+        assert!(events.contains(&DealerEvent::ActionTaken { seat: 1, t: ActionType::Fold, value: 0, amt: 0 }));
+
+        dealer.p_action(Action { seat: 2, t: ActionType::Call, value: 0 });
+        assert_eq!(dealer.curr, 3);
+        clock.advance(6000);
+        let action = dealer.poll_clock().unwrap();
+        assert_eq!((action.seat, action.t), (3, ActionType::Check));
+        assert_eq!(dealer.stage, Stages::Flop);
+    }
+}
@@ -0,0 +1,176 @@
+use crate::dealer::*;
+use crate::error::*;
+
+// table rules a Dealer is dealt with, Dealer::new uses the default
+// set once with Dealer::with_config, the hand in progress reads it from self.config
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct TableConfig {
+    // blinds new_hand posts, and an ante every player dealt in posts after them, 0 for none
+    pub small_blind: u16,
+    pub big_blind: u16,
+    pub ante: u16,
+    // raises allowed on each street, the bb posting the blind does not count, None is no cap
+    pub preflop_raise_cap: Option<u8>,
+    pub flop_raise_cap: Option<u8>,
+    // no cap once only two players are left in the hand
+    pub heads_up_uncapped: bool,
+    // chips a player can sit down or rebuy with, a top up cant take a stack over the max
+    pub min_buy_in: u16,
+    pub max_buy_in: u16,
+    // percent of the pot taken at showdown, see rake.rs, 0 for no rake
+    pub rake_percent: f32,
+    // (players dealt in, cap) the most rake a pot can pay, the entry with the most players up to the hand's count applies
+    pub rake_caps: Vec<(u8, u16)>,
+    // no rake when the hand ends before the flop
+    pub no_flop_no_drop: bool,
+    // ms a player has to act before its time bank starts, see clock.rs, None for no clock
+    pub action_time: Option<u64>,
+    // ms of time bank each player sits down with
+    pub time_bank: u64,
+}
+
+impl Default for TableConfig {
+    // the limits the dealer always had, 1/2 blinds, 4 raises preflop after the bb and 5 after a bet on the flop
+    fn default() -> Self {
+        TableConfig {
+            small_blind: SMALL_BLIND,
+            big_blind: BIG_BLIND,
+            ante: 0,
+            preflop_raise_cap: Some(4),
+            flop_raise_cap: Some(5),
+            heads_up_uncapped: false,
+            min_buy_in: 1,
+            max_buy_in: u16::MAX,
+            rake_percent: 0.0,
+            rake_caps: vec![],
+            no_flop_no_drop: true,
+            action_time: None,
+            time_bank: 0,
+        }
+    }
+}
+
+impl TableConfig {
+    // rules the dealer cant play with, with_config panics on them
+    pub fn validate(&self) -> Result<(), DealerError> {
+        // also false for NaN
+        if !(0.0..=100.0).contains(&self.rake_percent) {
+            return Err(DealerError::InvalidConfig("rake_percent"));
+        }
+        Ok(())
+    }
+}
+
+impl Dealer {
+    // raises allowed on the current street, None when there is no cap
+    pub fn raise_cap(&self) -> Option<u8> {
+        let live = self.p.iter().filter(|p| !p.is_folded).count();
+        if self.config.heads_up_uncapped && live <= 2 {
+            return None;
+        }
+        match self.stage {
+            Stages::PreFlop => self.config.preflop_raise_cap,
+            Stages::Flop => self.config.flop_raise_cap,
+            _ => None,
+        }
+    }
+
+    // true when the street has had as many raises as the cap allows
+    pub fn raise_capped(&self) -> bool {
+        self.raise_cap().is_some_and(|cap| self.raises >= cap)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::player::*;
+
+    fn raise(dealer: &mut Dealer, value: u16) -> Result<(), DealerError> {
+        let seat = dealer.curr;
+        dealer.try_p_action(Action { seat, t: ActionType::Raise, value })
+    }
+
+    #[test]
+    fn test_raise_cap() {
+        let config = TableConfig { preflop_raise_cap: Some(2), flop_raise_cap: Some(1), ..TableConfig::default() };
+        let mut dealer = Dealer::with_config(123, vec![
+            Player::new(1, 500),
+            Player::new(2, 500),
+            Player::new(3, 500),
+        ], config);
+        dealer.new_hand();
+        raise(&mut dealer, 2).unwrap();
+        raise(&mut dealer, 4).unwrap();
+        assert!(dealer.raise_capped());
+        assert_eq!(dealer.get_available_actions(), vec![ActionType::Fold, ActionType::Call]);
+        assert_eq!(raise(&mut dealer, 8), Err(DealerError::IllegalAction));
+        dealer.try_p_action(Action { seat: 3, t: ActionType::Call, value: 0 }).unwrap();
+        dealer.try_p_action(Action { seat: 1, t: ActionType::Call, value: 0 }).unwrap();
+        assert_eq!(dealer.stage, Stages::Flop);
+
+        // the count starts over on the flop, a bet is not a raise
+        assert!(!dealer.raise_capped());
+        let seat = dealer.curr;
+        dealer.try_p_action(Action { seat, t: ActionType::Bet, value: 2 }).unwrap();
+        raise(&mut dealer, 2).unwrap();
+        assert!(dealer.raise_capped());
+        assert_eq!(raise(&mut dealer, 4), Err(DealerError::IllegalAction));
+    }
+
+    #[test]
+    fn test_raise_cap_short_stacks() {
+        let config = TableConfig { preflop_raise_cap: Some(1), ..TableConfig::default() };
+        let mut dealer = Dealer::with_config(123, vec![
+            Player::new(1, 500),
+            Player::new(2, 30),
+            Player::new(3, 5),
+        ], config);
+        dealer.new_hand();
+        raise(&mut dealer, 4).unwrap();
+        assert!(dealer.raise_capped());
+        // more than the call, the cap leaves only a call
+        assert_eq!(dealer.get_available_actions(), vec![ActionType::Fold, ActionType::Call]);
+        dealer.try_p_action(Action { seat: 2, t: ActionType::Call, value: 0 }).unwrap();
+        // at or below the call, it can still call all in
+        assert_eq!(dealer.get_call_amt(&3), 4);
+        assert_eq!(dealer.get_available_actions(), vec![ActionType::Fold, ActionType::CallAI]);
+        dealer.try_p_action(Action { seat: 3, t: ActionType::CallAI, value: 0 }).unwrap();
+        assert_eq!(dealer.stage, Stages::Flop);
+    }
+
+    #[test]
+    #[should_panic(expected = "Raise cap reached")]
+    fn test_raise_cap_p_action() {
+        let config = TableConfig { preflop_raise_cap: Some(1), ..TableConfig::default() };
+        let mut dealer = Dealer::with_config(123, vec![
+            Player::new(1, 500),
+            Player::new(2, 500),
+            Player::new(3, 500),
+        ], config);
+        dealer.new_hand();
+        dealer.p_action(Action { seat: 1, t: ActionType::Raise, value: 2 });
+        dealer.p_action(Action { seat: 2, t: ActionType::Raise, value: 4 });
+    }
+
+    #[test]
+    fn test_heads_up_uncapped() {
+        let config = TableConfig { preflop_raise_cap: Some(1), flop_raise_cap: Some(1), heads_up_uncapped: true, ..TableConfig::default() };
+        let mut dealer = Dealer::with_config(123, vec![
+            Player::new(1, 500),
+            Player::new(2, 500),
+            Player::new(3, 500),
+        ], config);
+        dealer.new_hand();
+        raise(&mut dealer, 2).unwrap();
+        assert_eq!(dealer.raise_cap(), Some(1));
+        assert!(dealer.raise_capped());
+        dealer.try_p_action(Action { seat: 2, t: ActionType::Fold, value: 0 }).unwrap();
+        // down to two players, the cap is lifted
+        assert_eq!(dealer.raise_cap(), None);
+        raise(&mut dealer, 4).unwrap();
+        raise(&mut dealer, 8).unwrap();
+        assert_eq!(dealer.raises, 3);
+    }
+}
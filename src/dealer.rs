@@ -1,11 +1,29 @@
-use std::collections::HashMap;
 use std::fmt;
+use std::sync::Arc;
 
 use rs_handstrength::normalize_equity;
 use rs_handstrength::{Card, equity};
 use crate::player::*;
 use crate::deck::*;
+use crate::error::*;
+use crate::events::*;
+use crate::ledger::*;
+use crate::config::*;
+use crate::seating::*;
+use crate::clock::*;
+use crate::advance::*;
+
+// debug dumps before a panic, compiled out on wasm where there is no stderr
+macro_rules! debug_eprintln {
+    ($($arg:tt)*) => {
+        #[cfg(not(target_arch = "wasm32"))]
+        eprintln!($($arg)*);
+    };
+}
 
+// the default blinds, TableConfig has the ones new_hand posts
+pub const SMALL_BLIND: u16 = 1;
+pub const BIG_BLIND: u16 = 2;
 
 #[derive(Debug, Clone)]
 pub struct Dealer {
@@ -14,19 +32,49 @@ pub struct Dealer {
     pub stage: Stages,
     pub seed: u64,
     pub button: u8,
+    pub sb: u8,           // sb seat this hand, dead when it is not dealt in
+    pub bb: u8,
+    pub next_blinds: Option<(u8, u8)>,   // sb and bb for the next hand, set by move_button
     pub curr: u8,
     pub pot: u16,
     pub ah: ActionHistory,
     pub s_bets: Vec<SBet>,
     pub done_s_bets: Vec<SBet>,
-    pub flop: [Card; 3]
+    pub committed: Vec<Commitment>,
+    pub refunds: Vec<(u8, u16)>,
+    pub min_raise: u16,   // smallest full bet or raise this street, the size of the last full one
+    pub acted: Vec<u8>,   // seats that acted since the last full bet or raise, they cant raise again
+    pub short_raises: u16,   // incomplete raises since the last full one, together they can make a full raise
+    pub raises: u8,       // raises this street, not counting the bb
+    pub config: TableConfig,
+    pub hands: u32,                  // hands dealt so far
+    pub rake: u16,                   // taken by the showdown this hand
+    pub session: Vec<SessionEntry>,  // every buy in, top up, rebuy and cash out, oldest first
+    pub clock: Arc<dyn Clock>,       // only read when config.action_time is set
+    pub turn_started: Option<u64>,   // when curr's clock started
+    pub advance: Vec<(u8, AdvanceAction)>,   // queued by seats waiting to act
+    pub flop: [Card; 3],
+    pub events: Vec<DealerEvent>
 }
 
+// hand lifecycle, WaitingForHand -> PreFlop -> Flop -> AwaitingSettlement -> Settled -> PreFlop ...
+// the hand can skip Flop and go straight to AwaitingSettlement when everyone but one folds
 #[derive(Debug, Clone, PartialEq, Hash, Eq)]
 pub enum Stages {
+    WaitingForHand,
     PreFlop,
     Flop,
-    Showdown
+    // betting is over, handle_showdown has not paid out yet
+    AwaitingSettlement,
+    // handle_showdown has paid out, ready for new_hand
+    Settled
+}
+
+impl Stages {
+    // players can still act
+    pub fn is_betting(&self) -> bool {
+        matches!(self, Stages::PreFlop | Stages::Flop)
+    }
 }
 #[derive(Debug, Clone, PartialEq, Hash, Eq)]
 pub enum ActionType {
@@ -55,6 +103,24 @@ impl fmt::Display for ActionType {
     }
 }
 
+// parses the codes written by Display, eg "CK" or "RA"
+impl std::str::FromStr for ActionType {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "F" => Ok(ActionType::Fold),
+            "CK" => Ok(ActionType::Check),
+            "C" => Ok(ActionType::Call),
+            "CA" => Ok(ActionType::CallAI),
+            "B" => Ok(ActionType::Bet),
+            "BA" => Ok(ActionType::BetAI),
+            "R" => Ok(ActionType::Raise),
+            "RA" => Ok(ActionType::RaiseAI),
+            _ => Err(format!("unknown action type {:?}", s)),
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct Action {
     pub seat: u8,
@@ -85,13 +151,33 @@ pub struct ActionHistory {
     pub start_bal: Vec<StartingBal>,
     pub actions: Vec<Action>,
     pub pf: Vec<usize>,
-    pub f: Vec<usize>
+    pub f: Vec<usize>,
+    pub voided: bool   // the hand was aborted by void_hand and everyone got their start_bal back
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct SidePot {
     pub value: u16,          // Total amount in the side pot
-    pub contributors: Vec<u8> // Seats of the players who contributed to the pot
+    pub contributors: Vec<u8> // Seats of the players still in that can win the pot, in hand order
+}
+
+// one pot paid out at showdown
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct PotResult {
+    pub value: u16,                // Total chips in the pot
+    pub eligible: Vec<u8>,         // Seats that could win it, in hand order
+    pub equities: Vec<(u8, f32)>,  // Equity of each eligible seat for this pot, out of 100
+    pub awards: Vec<(u8, u16)>     // Chips paid from this pot to each eligible seat, odd chips included
+}
+
+// what handle_showdown did, main pot first
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct ShowdownResult {
+    pub pots: Vec<PotResult>,
+    pub awarded: Vec<(u8, u16)>,   // Total chips won per seat
+    pub refunds: Vec<(u8, u16)>,   // Uncalled bets given back by refund_excess this hand, at the end of each street
+    pub odd_chips: Vec<(u8, u16)>, // Chips left over from rounding equity shares and who got them
+    pub rake: u16                  // Taken out before the pots were split, see rake.rs
 }
 // button doesnt move, we can just random the chips across diff hands
 impl Dealer {
@@ -109,26 +195,68 @@ impl Dealer {
         }
         smallest_seat
     }
+    // the seed the current or last hand's deck was shuffled with, seed itself before the first hand
+    pub fn hand_seed(&self) -> u64 {
+        self.seed.wrapping_add(self.hands.saturating_sub(1) as u64)
+    }
+
     // Constructor: Initializes a new Dealer with an empty list of p
     pub fn new(seed: u64, p: Vec<Player>) -> Self {
+        Dealer::with_config(seed, p, TableConfig::default())
+    }
+
+    // new with the table rules in config instead of the defaults
+    pub fn with_config(seed: u64, mut p: Vec<Player>, config: TableConfig) -> Self {
+        if let Err(e) = config.validate() {
+            panic!("with_config {}", e);
+        }
+        for player in &mut p {
+            player.time_bank = config.time_bank;
+        }
         let button = Dealer::find_smallest_seat(&p);
+        let session = p.iter().map(|p| SessionEntry {
+            hands: 0,
+            seat: p.seat,
+            change: ChipChange::BuyIn,
+            amt: p.chips,
+            chips: p.chips
+        }).collect();
+        let min_raise = config.big_blind;
         Dealer {
             p,
             deck: Deck::new(seed),
-            stage: Stages::Showdown,
+            stage: Stages::WaitingForHand,
             seed,
             button,
+            sb: 0,
+            bb: 0,
+            next_blinds: None,
             pot: 0,
             curr: 0,
             ah: ActionHistory {
                 start_bal: vec![],
                 actions: vec![],
                 pf: vec![],
-                f: vec![]
+                f: vec![],
+                voided: false
             },
             s_bets: vec![],
             done_s_bets: vec![],
+            committed: vec![],
+            refunds: vec![],
+            min_raise,
+            acted: vec![],
+            short_raises: 0,
+            raises: 0,
+            config,
+            hands: 0,
+            rake: 0,
+            session,
+            clock: Arc::new(SystemClock),
+            turn_started: None,
+            advance: vec![],
             flop: [Card::default(); 3],
+            events: vec![],
         }
     }
     //  order p by seat according to the button
@@ -136,87 +264,144 @@ impl Dealer {
     // there should be no return, it would modify the p Vec<Player> in place
     // sort the player seats by seat number first, then deal with the p before and after the button seperately to get the right order
     // are you able to do this without cloning the p to avoid unnecessary memory usage?
+    // heads up the button is first instead, it posts the small blind and acts first preflop
+    // the bb is then left of the button and acts first on the flop
     pub fn order_p(&mut self) {
         // Sort p by seat
         self.p.sort_by_key(|p| p.seat);
 
         // Find the index of the player to the left of the button
-        let index = self.p.iter().position(|p| p.seat == self.button)
-            .expect("Button not found") + 1;
+        // the button can be dead on a seat nobody is in
+        let mut index = self.p.iter().position(|p| p.seat > self.button).unwrap_or(0);
+        if self.p.iter().filter(|p| p.can_play(&self.config)).count() == 2 {
+            if let Some(i) = self.p.iter().position(|p| p.seat == self.button) {
+                index = i;
+            }
+        }
 
         // Rotate p so that the player to the left of the button is at the front
-        let num_p = self.p.len(); 
-        self.p.rotate_left(index % num_p);
+        self.p.rotate_left(index);
     } 
   
 
     // new hand function
-    // does not support calling a second time as it doesnt reset the hand and p states
+    // resets the hand and p states so the same dealer can deal hand after hand
+    // players sitting out or owing more blinds than they have are folded before the deal
+    // a player with no chips left is sat out
+    // each hand gets its own deck, shuffled with seed plus the number of hands dealt before it
     pub fn new_hand(&mut self) {
-        self.deck = Deck::new(self.seed);
+        self.deck = Deck::new(self.seed.wrapping_add(self.hands as u64));
         self.order_p();
         match self.stage {
-            Stages::Showdown => {
+            Stages::WaitingForHand | Stages::Settled => {
                 for player in &mut self.p {
-                    player.is_folded = false;
-                    player.is_all_in = false;
-                    player.hand = self.deck.draw4();
                     if player.chips < 1 {
-                        panic!("Player does not have enough chips");
+                        player.sitting_out = true;
                     }
+                    player.is_folded = !player.can_play(&self.config);
+                    player.is_all_in = false;
+                    player.hand = if player.is_folded { [Card::default(); 4] } else { self.deck.draw4() };
                 }
             }
+            Stages::AwaitingSettlement => panic!("new_hand Hand not settled"),
             _ => panic!("new_hand Hand not over"),
         };
-        self.curr = self.p[0].seat;
-        self.stage = Stages::PreFlop;
+        let dealt = self.p.iter().filter(|p| !p.is_folded).map(|p| p.seat).collect::<Vec<u8>>();
+        if dealt.len() < 2 {
+            panic!("new_hand Not enough players");
+        }
+        (self.sb, self.bb) = self.take_blinds(&dealt);
+        self.hands += 1;
+        self.curr = if dealt.contains(&self.sb) { self.sb } else { self.bb };
         self.pot = 0;
         self.flop = [Card::default(); 3];
         self.s_bets = vec![];
         self.done_s_bets = vec![];
+        self.committed = dealt.iter().map(|seat| Commitment::new(*seat)).collect();
+        self.refunds = vec![];
+        self.rake = 0;
+        self.advance = vec![];
+        self.min_raise = self.config.big_blind;
+        self.acted = vec![];
+        self.short_raises = 0;
+        self.raises = 0;
+        self.ah.actions = vec![];
+        self.ah.pf = vec![];
+        self.ah.f = vec![];
+        self.ah.voided = false;
         // update action history starting bal
-        self.ah.start_bal = self.p.iter().map(|p| StartingBal {
+        self.ah.start_bal = self.p.iter().filter(|p| !p.is_folded).map(|p| StartingBal {
             seat: p.seat,
             bal: p.chips
         }).collect::<Vec<StartingBal>>();
+        self.events = vec![];
+        self.emit(DealerEvent::HandStarted { button: self.button, seats: self.ah.start_bal.clone() });
+        for i in 0..self.p.len() {
+            if !self.p[i].is_folded {
+                self.emit(DealerEvent::CardsDealt { seat: self.p[i].seat, hand: self.p[i].hand });
+            }
+        }
+        self.set_stage(Stages::PreFlop);
 
-        // first player to post small blind by p_action bet 1
+        // sb posts by p_action bet and the bb raises up to the bb
         // println!("new hand {:?}", self.ah.start_bal);
-        if self.ah.start_bal[0].bal > 1 {
-            self.p_action(Action {
-                seat: self.ah.start_bal[0].seat,
-                t: ActionType::Bet,
-                value: 1
-            });
-        } else {
-            self.p_action(Action {
-                seat: self.ah.start_bal[0].seat,
-                t: ActionType::BetAI,
-                value: self.ah.start_bal[0].bal
-            });
+        let (small_blind, big_blind) = (self.config.small_blind, self.config.big_blind);
+        if self.sb_live() {
+            let sb_chips = self.p.iter().find(|p| p.seat == self.sb).unwrap().chips;
+            if sb_chips > small_blind {
+                self.act(Action {
+                    seat: self.sb,
+                    t: ActionType::Bet,
+                    value: small_blind
+                }, true);
+            } else {
+                self.act(Action {
+                    seat: self.sb,
+                    t: ActionType::BetAI,
+                    value: sb_chips
+                }, true);
+            }
         }
 
-        // second player to post bb
-        if self.ah.start_bal[1].bal > 2 {
-            self.p_action(Action {
-                seat: self.ah.start_bal[1].seat,
+        // bb posts, by itself it bets the whole bb
+        let bb_chips = self.p.iter().find(|p| p.seat == self.bb).unwrap().chips;
+        // a short sb can post less than the sb
+        let sb_posted = self.committed.iter().find(|c| c.seat == self.sb).map_or(0, |c| c.live());
+        if !self.sb_live() {
+            if bb_chips > big_blind {
+                self.act(Action {
+                    seat: self.bb,
+                    t: ActionType::Bet,
+                    value: big_blind
+                }, true);
+            } else {
+                self.act(Action {
+                    seat: self.bb,
+                    t: ActionType::BetAI,
+                    value: bb_chips
+                }, true);
+            }
+        } else if bb_chips > big_blind {
+            self.act(Action {
+                seat: self.bb,
                 t: ActionType::Raise,
-                value: 1
-            });
-        } else if self.ah.start_bal[1].bal == 1 {
-            self.p_action(Action {
-                seat: self.ah.start_bal[1].seat,
+                value: big_blind - sb_posted
+            }, true);
+        } else if bb_chips <= sb_posted {
+            self.act(Action {
+                seat: self.bb,
                 t: ActionType::CallAI,
                 value: 0
-            });
+            }, true);
         } else {
-            self.p_action(Action {
-                seat: self.ah.start_bal[1].seat,
+            self.act(Action {
+                seat: self.bb,
                 t: ActionType::RaiseAI,
-                value: 1
-            });
+                value: bb_chips - sb_posted
+            }, true);
         }
-
+        self.post_missed_blinds();
+        self.post_antes();
 
     }
 
@@ -242,6 +427,7 @@ impl Dealer {
 
     pub fn add_chips_to_pot(&mut self, seat: &u8, amt: &u16) {
         self.remove_chips_from_player(seat, amt);
+        self.commit(seat, amt);
         self.pot += amt;
     }
 
@@ -251,7 +437,7 @@ impl Dealer {
     pub fn pay_from_pot(&mut self, seat: &u8, amt: &u16) {
         self.add_chips_to_player(seat, amt);
         if self.pot < *amt {
-            eprintln!("Pot {:?} amt {:?}", self.pot, amt);
+            debug_eprintln!("Pot {:?} amt {:?}", self.pot, amt);
             // println!("ah {:?}", self.ah);
             panic!("Not enough chips in pot");
         }
@@ -260,29 +446,73 @@ impl Dealer {
     // allows the player to make actions
     // should take in self and an action struct
 
+    // plays any advance actions that come up after it, see advance.rs
     pub fn p_action(&mut self, action: Action) {
+        self.act(action, false);
+        self.run_advance();
+    }
+
+    // p_action, queueing a BlindPosted instead of an ActionTaken event for the blinds
+    // the event goes in before whatever update_stage queues, with the chips the action cost
+    pub(crate) fn act(&mut self, action: Action, blind: bool) {
+        let at = self.events.len();
+        let seat = action.seat;
+        let before = self.p.iter().find(|p| p.seat == seat).map(|p| p.chips).unwrap_or(0);
+        let (t, value) = (action.t.clone(), action.value);
+        if !blind {
+            self.charge_time();
+        }
+        self.apply_action(action, blind);
+        self.start_turn();
+        // an uncalled bet can come back to the seat in the same action when it closes the street
+        let returned = self.events[at..].iter().map(|e| match e {
+            DealerEvent::BetReturned { seat: s, amt } if *s == seat => *amt,
+            _ => 0,
+        }).sum::<u16>();
+        let amt = before + returned - self.p.iter().find(|p| p.seat == seat).unwrap().chips;
+        let event = if blind {
+            DealerEvent::BlindPosted { seat, amt }
+        } else {
+            DealerEvent::ActionTaken { seat, t, value, amt }
+        };
+        self.events.insert(at, event);
+    }
+
+    fn apply_action(&mut self, action: Action, blind: bool) {
         // println!("p action {:?}", action);
         // not all rules are checked, im lazy and it runs faster without it
         // makes it hard to catch errors
         // @TODO should check for errors by parsing ActionHistory to see if rules are broken 
+        if self.stage == Stages::WaitingForHand {
+            panic!("No hand in progress");
+        }
+        if !self.stage.is_betting() {
+            panic!("Hand is over");
+        }
         if self.curr != action.seat {
             panic!("Not your turn");
         }
-        if self.stage == Stages::Showdown {
-            panic!("Hand is over");
+        if !blind && matches!(action.t, ActionType::Raise | ActionType::RaiseAI) {
+            if let Some(reason) = self.raise_blocked(action.seat) {
+                debug_eprintln!("action {:#?} acted {:?} raises {}", action, self.acted, self.raises);
+                panic!("{}", reason);
+            }
         }
         let call_amt = self.get_call_amt(&action.seat);
         let p_chips = self.p.iter().find(|p| p.seat == action.seat).unwrap().chips;
+        if !blind {
+            self.track_raise(&action);
+        }
         match action.t {
             ActionType::Call => {
                 if call_amt > p_chips {
-                                        eprintln!("debug {:#?}", self);
-                    eprintln!("action {:#?}", action);
+                                        debug_eprintln!("debug {:#?}", self);
+                    debug_eprintln!("action {:#?}", action);
                     panic!("Not enough chips");
                 }
                 if action.value != 0 {
-                                        eprintln!("debug {:#?}", self);
-                    eprintln!("action {:#?}", action);
+                                        debug_eprintln!("debug {:#?}", self);
+                    debug_eprintln!("action {:#?}", action);
                     panic!("Call action should not have value");
                 }
                 // pay off outstanding bets
@@ -290,8 +520,8 @@ impl Dealer {
                     self.pay_all_outstanding_bets(&action.seat, &p_chips);
                     self.clean_s_bets();
                 } else {
-                                        eprintln!("debug {:#?}", self);
-                    eprintln!("action {:#?}", action);
+                                        debug_eprintln!("debug {:#?}", self);
+                    debug_eprintln!("action {:#?}", action);
                     panic!("Call No outstanding bets to call");
                 }
 
@@ -313,13 +543,13 @@ impl Dealer {
                 // there may be bets that the callAI amt is not enough to cover
                 // it needs to go into the partial paid vec then
                 if action.value != 0 {
-                                        eprintln!("debug {:#?}", self);
-                    eprintln!("action {:#?}", action);
+                                        debug_eprintln!("debug {:#?}", self);
+                    debug_eprintln!("action {:#?}", action);
                     panic!("CallAI action should not have value");
                 }
                 if call_amt < p_chips {
-                                        eprintln!("debug {:#?}", self);
-                    eprintln!("action {:#?}", action);
+                                        debug_eprintln!("debug {:#?}", self);
+                    debug_eprintln!("action {:#?}", action);
                     panic!("cant callAI too many chips");
                 }
                 // pay off outstanding bets
@@ -330,8 +560,8 @@ impl Dealer {
                 } else {
                     // println!("gona painc callAmt is 0 {:?} {:?} {:?}", self.stage, action, self.ah);
                     // println!("s_bets {:?} done_s_bets {:?}", self.s_bets, self.done_s_bets);
-                                        eprintln!("debug {:#?}", self);
-                    eprintln!("action {:#?}", action);
+                                        debug_eprintln!("debug {:#?}", self);
+                    debug_eprintln!("action {:#?}", action);
                     panic!("call AI No outstanding bets to call");
                 }
                 let player = self.p.iter_mut().find(|p| p.seat == action.seat).unwrap();
@@ -399,14 +629,14 @@ impl Dealer {
             },
             ActionType::Bet => {
                 if &action.value + call_amt > p_chips {
-                                        eprintln!("debug {:#?}", self);
-                    eprintln!("action {:#?}", action);
+                                        debug_eprintln!("debug {:#?}", self);
+                    debug_eprintln!("action {:#?}", action);
                     panic!("Not enough chips");
                 }
                 // pay off outstanding bets
                 if call_amt > 0 {
-                                        eprintln!("debug {:#?}", self);
-                    eprintln!("action {:#?}", action);
+                                        debug_eprintln!("debug {:#?}", self);
+                    debug_eprintln!("action {:#?}", action);
                     panic!("Bet should not have outstanding bets");
                 } 
                 // pay the bet
@@ -421,6 +651,8 @@ impl Dealer {
                     unpaid: not_player,
                     pp: vec![]
                 });
+                // nobody left who can call it, eg everyone else is all in
+                self.clean_s_bets();
                 match self.stage {
                     Stages::PreFlop => {
                         self.ah.pf.push(index);
@@ -436,13 +668,13 @@ impl Dealer {
             ActionType::BetAI => {
 
                 if &action.value != &p_chips {
-                    eprintln!("action value {:?} p_chips {:?}", action.value, p_chips);
+                    debug_eprintln!("action value {:?} p_chips {:?}", action.value, p_chips);
                     panic!("BetAI should bet all in");
                 }
                 // pay off outstanding bets
                 if call_amt > 0 {
-                                        eprintln!("debug {:#?}", self);
-                    eprintln!("action {:#?}", action);
+                                        debug_eprintln!("debug {:#?}", self);
+                    debug_eprintln!("action {:#?}", action);
                     panic!("Bet should not have outstanding bets");
                 } 
                 // pay the bet
@@ -461,6 +693,8 @@ impl Dealer {
                     unpaid: not_player,
                     pp: vec![]
                 });
+                // nobody left who can call it, eg everyone else is all in
+                self.clean_s_bets();
                 match self.stage {
                     Stages::PreFlop => {
                         self.ah.pf.push(index);
@@ -477,13 +711,14 @@ impl Dealer {
                 if &action.value + call_amt > p_chips {
                     // println!("raise called {:?} {:?}", call_amt, action);
                     // println!("pchips {:?} pot {:?}", p_chips, self.pot);
-                                        eprintln!("debug {:#?}", self);
-                    eprintln!("action {:#?}", action);
+                                        debug_eprintln!("debug {:#?}", self);
+                    debug_eprintln!("action {:#?}", action);
                     panic!("raise Not enough chips");
                 }
-                if &action.value > &(call_amt + self.pot) {
-                                        eprintln!("debug {:#?}", self);
-                    eprintln!("action {:#?}", action);
+                // the bb is posted whatever the pot, a short sb leaves it small
+                if !blind && action.value > call_amt + self.pot {
+                                        debug_eprintln!("debug {:#?}", self);
+                    debug_eprintln!("action {:#?}", action);
                     panic!("raise too much");
                 }
                 // println!("raise called {:?} {:?}", call_amt, action);
@@ -497,6 +732,11 @@ impl Dealer {
                 // println!("paying off raise now {:?} {:?}", action, self.s_bets); 
                 // pay the bet
                 self.add_chips_to_pot(&action.seat, &action.value);
+                // a pot sized raise can take the whole stack
+                let player = self.p.iter_mut().find(|p| p.seat == action.seat).unwrap();
+                if player.chips == 0 {
+                    player.is_all_in = true;
+                }
 
                 let not_player: Vec<u8> = self.p.iter().filter(|p| p.seat != action.seat && !p.is_all_in && !p.is_folded).map(|p| p.seat).collect::<Vec<u8>>();
                 // index before push, no need to - 1
@@ -507,6 +747,8 @@ impl Dealer {
                     unpaid: not_player,
                     pp: vec![]
                 });
+                // nobody left who can call it, eg everyone else is all in
+                self.clean_s_bets();
                 match self.stage {
                     Stages::PreFlop => {
                         self.ah.pf.push(index);
@@ -521,13 +763,13 @@ impl Dealer {
             },
             ActionType::RaiseAI => {
                 if &action.value + call_amt != p_chips {
-                    eprintln!("debug {:#?}", self);
-                    eprintln!("action {:#?} call_amt {:?}", action, call_amt);
+                    debug_eprintln!("debug {:#?}", self);
+                    debug_eprintln!("action {:#?} call_amt {:?}", action, call_amt);
                     panic!("raiseai incorrect chips");
                 }
-                if &action.value > &(call_amt + self.pot) {
-                                        eprintln!("debug {:#?}", self);
-                    eprintln!("action {:#?}", action);
+                if !blind && action.value > call_amt + self.pot {
+                                        debug_eprintln!("debug {:#?}", self);
+                    debug_eprintln!("action {:#?}", action);
                     
                     panic!("raiseAI too much");
                 }
@@ -556,6 +798,8 @@ impl Dealer {
                     unpaid: not_player,
                     pp: vec![]
                 });
+                // nobody left who can call it, eg everyone else is all in
+                self.clean_s_bets();
                 match self.stage {
                     Stages::PreFlop => {
                         self.ah.pf.push(index);
@@ -586,6 +830,9 @@ impl Dealer {
     // if it is empty, check if there are any active players who are not folded and not all in
 
     pub fn update_stage(&mut self) {
+        if !self.stage.is_betting() {
+            panic!("update_stage Hand not in betting {:?}", self.stage);
+        }
         let s_bets_len = self.s_bets.len();
         let ap_count = self.p.iter().filter(|p| !p.is_folded && !p.is_all_in).count();
         // println!("update stage {:?} {:?}", self.stage, self.s_bets);
@@ -599,9 +846,9 @@ impl Dealer {
                 let last_action = &self.ah.actions[num_a - 1];
                 let is_last_action_bb_check = 
                     last_action.t == ActionType::Check &&
-                    last_action.seat == self.ah.start_bal[1].seat;
+                    last_action.seat == self.bb;
 
-                if !is_last_action_bb_check && self.done_s_bets.len() == 2 && ap_count > 1 {
+                if !is_last_action_bb_check && self.done_s_bets.len() == self.blind_bets() && ap_count > 1 {
                     // the last bet was the bb
                     self.next_player();
                 } else {
@@ -613,9 +860,9 @@ impl Dealer {
     
                     // println!("dealt flop {:?}", ap_count);
                     if ap_count <= 1 {
-                        self.stage = Stages::Showdown;
+                        self.set_stage(Stages::AwaitingSettlement);
                     } else {
-                        self.stage = Stages::Flop;
+                        self.set_stage(Stages::Flop);
                         self.curr = self.button;
                         self.next_player();
                     }
@@ -625,7 +872,7 @@ impl Dealer {
             } else {
                 // println!("flop s_bets {:?} all_s_bets {:?}", self.s_bets, self.done_s_bets);
                 if ap_count <= 1 {
-                    self.stage = Stages::Showdown;
+                    self.set_stage(Stages::AwaitingSettlement);
                 } else {
                     let flop_actions: &[Action] = &self.ah.actions[self.ah.f[0]..];
                     // println!("flop actions {:?}", self.ah);
@@ -636,17 +883,17 @@ impl Dealer {
                         let num_checks = flop_actions.iter().filter(|a| a.t == ActionType::Check).count();
                         // println!("num checks {:?} ap {:?}", num_checks, ap_count);
                         if num_checks > ap_count {
-                            eprintln!("flop actions {:?}", flop_actions);
-                            eprintln!("ah {:#?}", self.ah);
+                            debug_eprintln!("flop actions {:?}", flop_actions);
+                            debug_eprintln!("ah {:#?}", self.ah);
                             panic!("too many checks");
                         }
                         if num_checks == ap_count {
-                            self.stage = Stages::Showdown;
+                            self.set_stage(Stages::AwaitingSettlement);
                         } else {
                             self.next_player();
                         }
                     } else {
-                        self.stage = Stages::Showdown;
+                        self.set_stage(Stages::AwaitingSettlement);
                     }
                 }
   
@@ -664,14 +911,13 @@ impl Dealer {
 
     pub fn next_player(&mut self) {
         let curr_seat = self.curr;
-        let num_p = self.p.len() as u8;
-        let mut next = curr_seat % num_p + 1;
-        while self.p.iter().any(|p| p.seat == next && (p.is_folded || p.is_all_in)) {
-            next = next % num_p + 1;
-        }
+        // going round by seat number, there can be empty seats and curr can be a dead button
+        let mut seats = self.p.iter().filter(|p| !p.is_folded && !p.is_all_in).map(|p| p.seat).collect::<Vec<u8>>();
+        seats.sort();
+        let next = seats.iter().find(|s| **s > curr_seat).or(seats.first()).copied().unwrap_or(curr_seat);
 
         if next == curr_seat && !(
-            self.done_s_bets.len() == 2 && self.stage == Stages::PreFlop 
+            self.done_s_bets.len() == self.blind_bets() && self.stage == Stages::PreFlop 
         ){
             panic!("All players have folded or are all in. stage {:?} s_bets {:?} done_bets {:?}", self.stage, self.s_bets, self.done_s_bets);
         }
@@ -679,6 +925,11 @@ impl Dealer {
         self.curr = next;
     }
 
+    // bets the blinds made, 1 when the sb is dead
+    fn blind_bets(&self) -> usize {
+        if self.sb_live() { 2 } else { 1 }
+    }
+
 
     // get_call_amt func to get the amount to call based on s_bets and supplied seat
     // should take in self and a seat number
@@ -723,8 +974,8 @@ impl Dealer {
                             seat: *seat,
                             amt: i_chips
                         });
-                        i_chips = 0;
                         total += i_chips;
+                        i_chips = 0;
                     }
                     action.unpaid.retain(|x| x != seat);
                 } else {
@@ -769,21 +1020,25 @@ impl Dealer {
     // handle showdown
     // should take in self
     // should use rs_handstrength::equity to calculate the equity of each player
-    // pay out the pot by equity to each player
-    // should not return anything
+    // each side pot is paid out by equity among the players that contributed to it and did not fold
+    // returns what happened so callers dont have to diff chip counts
 
-    pub fn handle_showdown(&mut self) {
-        if self.stage != Stages::Showdown {
-            panic!("showdown Hand not over");
+    pub fn handle_showdown(&mut self) -> ShowdownResult {
+        match self.stage {
+            Stages::AwaitingSettlement => {}
+            Stages::Settled => panic!("showdown Hand already settled"),
+            _ => panic!("showdown Hand not over"),
         }
-        // we need to clone ah and replace it, below mutates ah.actions which sux for re-running hands
-        let tmp_actions = self.ah.actions.clone();
         // println!("handle showdown done s bets {:?}", self.done_s_bets);
+        // the street close already gave back uncalled bets, this is a no op unless the ledger was changed by hand
         self.refund_excess();
+        let mut result = ShowdownResult { refunds: self.refunds.clone(), ..Default::default() };
         // println!("after refund done s bets {:?}", self.done_s_bets);
         // println!("ah {:?}", self.ah);
         // println!("after refund players {:?}", self.p);
         let showdown_players_seats: Vec<u8> = self.p.iter().filter(|p| !p.is_folded).map(|p| p.seat).collect::<Vec<u8>>();
+        let sidepots = self.take_rake(self.group_side_pots());
+        result.rake = self.rake;
         let pot = self.pot;
         // println!("showdown_players_seats {:?}", showdown_players_seats);
         if showdown_players_seats.is_empty() {
@@ -794,26 +1049,68 @@ impl Dealer {
             let winner = showdown_players_seats[0];
             // println!("only one player {:?}", winner);
             self.pay_from_pot(&winner, &pot);
+            self.emit(DealerEvent::PotAwarded { seat: winner, amt: pot });
+            result.pots.push(PotResult {
+                value: pot,
+                eligible: vec![winner],
+                equities: vec![(winner, 100.0)],
+                awards: vec![(winner, pot)],
+            });
         } else {
             if self.flop[0].value == 0 {
                 panic!("Flop not dealt");
             }
-            let showdown_player_hands: Vec<[Card; 4]> = self.p.iter().filter(|p| !p.is_folded).map(|p| p.hand).collect::<Vec<[Card; 4]>>();
-            // println!("showdown player hands {:?}", showdown_player_hands);
-            // println!("showdown pseats {:?}", showdown_players_seats);
-            let equities = normalize_equity(&equity(&showdown_player_hands, &self.flop));
-            // println!("equities {:?}", equities);
-            
-            let sidepots = self.group_side_pots();
             // println!("sidepots {:?}", sidepots);
+            self.emit(DealerEvent::SidePotsFormed { pots: sidepots.clone() });
             for sidepot in sidepots {
+                let total = sidepot.value;
+                // in hand order so odd chips go to the first player left of the button
+                let mut eligible = showdown_players_seats.iter()
+                    .filter(|seat| sidepot.contributors.contains(seat))
+                    .cloned()
+                    .collect::<Vec<u8>>();
+                if eligible.is_empty() {
+                    // everyone that put chips in folded, the players still in split it
+                    eligible = showdown_players_seats.clone();
+                }
+                let equities = if eligible.len() == 1 {
+                    vec![100_f32]
+                } else {
+                    let hands = eligible.iter()
+                        .map(|seat| self.p.iter().find(|p| p.seat == *seat).unwrap().hand)
+                        .collect::<Vec<[Card; 4]>>();
+                    normalize_equity(&equity(&hands, &self.flop))
+                };
+                let mut pot_result = PotResult {
+                    value: total,
+                    eligible: eligible.clone(),
+                    equities: eligible.iter().cloned().zip(equities.iter().cloned()).collect(),
+                    awards: vec![],
+                };
+                let mut paid = 0;
                 for (i, equity) in equities.iter().enumerate() {
-                    let sidepot_total = sidepot.value * sidepot.contributors.len() as u16;
-                    let chips = (*equity as f32 / 100_f32 * sidepot_total as f32).floor() as u16;
-                    // println!("pot {:?} sidepot_total {:?} equity {:?} chips {:?}", self.pot, sidepot_total, equity, chips);
+                    let chips = (equity / 100_f32 * total as f32).floor() as u16;
+                    // println!("pot {:?} total {:?} equity {:?} chips {:?}", self.pot, total, equity, chips);
                     let min = std::cmp::min(chips, self.pot);
-                    self.pay_from_pot(&showdown_players_seats[i], &min);
+                    self.pay_from_pot(&eligible[i], &min);
+                    paid += min;
+                    pot_result.awards.push((eligible[i], min));
+                }
+                // rounding leftovers, one chip at a time in hand order
+                let mut i = 0;
+                while paid < total && self.pot > 0 {
+                    self.pay_from_pot(&eligible[i % eligible.len()], &1);
+                    pot_result.awards[i % eligible.len()].1 += 1;
+                    result.odd_chips.push((eligible[i % eligible.len()], 1));
+                    paid += 1;
+                    i += 1;
+                }
+                for (seat, amt) in &pot_result.awards {
+                    if *amt > 0 {
+                        self.emit(DealerEvent::PotAwarded { seat: *seat, amt: *amt });
+                    }
                 }
+                result.pots.push(pot_result);
             }
 
             if self.pot > 0 {
@@ -822,11 +1119,27 @@ impl Dealer {
                     panic!("isnt remainder pot {:?}", self.pot);
                 }
                 // println!("has remaining pot {:?}", self.pot);
-                self.pay_from_pot(&showdown_players_seats[0], &self.pot.clone());
+                let rest = self.pot;
+                self.pay_from_pot(&showdown_players_seats[0], &rest);
+                self.emit(DealerEvent::PotAwarded { seat: showdown_players_seats[0], amt: rest });
+                result.odd_chips.push((showdown_players_seats[0], rest));
+                result.awarded.push((showdown_players_seats[0], rest));
             }
         }
         // println!("got to end of showdown");
-        self.ah.actions = tmp_actions;
+        for pot in &result.pots {
+            for (seat, amt) in &pot.awards {
+                match result.awarded.iter_mut().find(|(s, _)| s == seat) {
+                    Some(a) => a.1 += amt,
+                    None => result.awarded.push((*seat, *amt)),
+                }
+            }
+        }
+        result.awarded.retain(|(_, amt)| *amt > 0);
+        self.set_stage(Stages::Settled);
+        let chips = self.p.iter().map(|p| StartingBal { seat: p.seat, bal: p.chips }).collect();
+        self.emit(DealerEvent::HandEnded { chips });
+        result
     }
 
     // helper function to deal 3 cards to the self.flop
@@ -838,117 +1151,59 @@ impl Dealer {
             panic!("Not preflop");
         }
         self.flop = self.deck.draw3();
+        self.emit(DealerEvent::FlopDealt { flop: self.flop });
     }
 
-    pub fn group_side_pots(&self) -> Vec<SidePot> {
-        // println!("group side pots called {:?}", &self.done_s_bets);
-        let mut side_pots: HashMap<Vec<u8>,SidePot> = HashMap::new(); 
-        // first break up all the partial paid into their own bets
-        // to break it up you
-            // first find the smallest partial paid value
-            // deduct smallestValue from that action's value and ALL its partial paid values
-            // create a new action with the smallestValue, with its old paid vec + ALL partial paid seats
-            // run the loop until there is no more partial paid
-            // this bascially breaks the bet/raise into multiple smaller bets/raises that have NO partial paids.
-            // we dont care about the unpaid field at this point
-        // then we need merge all the bets that have the same sorted paid vec
-
-        // println!("group sidepots called {:#?}", &self.done_s_bets);
-        for bet in &self.done_s_bets {
-            if !bet.pp.is_empty() {
-                // Partially paid bets
-                let mut ppbet = bet.clone();
-                let mut ppbet_value = self.ah.actions[ppbet.a].value;
-                // println!("inital ppbet {:?} ppbet_value {:?}", ppbet, ppbet_value);
-                while !ppbet.pp.is_empty() {
-                   
-                    let smallest_value = ppbet.pp.iter().min_by(|a, b| a.amt.cmp(&b.amt)).unwrap().amt;
-                    // println!("found pp {:?} smallest value {:?}", ppbet, smallest_value);
-                    let mut new_paid: Vec<u8> = bet.paid.clone();
-                    new_paid.extend(bet.pp.iter().map(|pp| pp.seat));
-                    new_paid.sort();
-                    side_pots.entry(new_paid.clone())
-                        .and_modify(|e| e.value += smallest_value)
-                        .or_insert(SidePot { value: smallest_value, contributors: new_paid }); 
-
-                    ppbet.pp = ppbet.pp.iter().map(|pp| PartialPaid {
-                        seat: pp.seat,
-                        amt: pp.amt - smallest_value
-                    }).filter(|pp| pp.amt != 0).collect::<Vec<PartialPaid>>();
-                    // println!("done pp {:?} smallest value {:?}", ppbet, smallest_value);
-                    ppbet_value -= smallest_value;
-                }
-                // println!("ppbet {:?} ppbet_value {:?}", ppbet, ppbet_value);
-                // add original bet to side pots
-                let mut new_paid: Vec<u8> = bet.paid.clone();
-                new_paid.sort();
-                side_pots.entry(new_paid.clone())
-                    .and_modify(|e| e.value += ppbet_value)
-                    .or_insert(SidePot { value: ppbet_value, contributors: new_paid });
-
-            } else {
-                // Fully paid bets
-                let mut new_paid: Vec<u8> = bet.paid.clone();
-                new_paid.sort();
-                side_pots.entry(new_paid.clone())
-                    .and_modify(|e| e.value += self.ah.actions[bet.a].value)
-                    .or_insert(SidePot { value: self.ah.actions[bet.a].value, contributors: new_paid });
-            }
-
- 
+    // sets the stage, queueing a StreetChanged event when it changes
+    // leaving a betting stage for Flop or AwaitingSettlement closes the street, giving back uncalled bets
+    fn set_stage(&mut self, stage: Stages) {
+        if self.stage.is_betting() && matches!(stage, Stages::Flop | Stages::AwaitingSettlement) {
+            self.close_street();
+        }
+        if self.stage != stage {
+            self.stage = stage.clone();
+            self.emit(DealerEvent::StreetChanged { stage });
         }
-        // println!("side pots {:?}", side_pots);
-        side_pots.values().cloned().collect()
     }
 
-    pub fn refund_excess(&mut self) {
-        if self.stage != Stages::Showdown {
-            panic!("refund Hand not over");
+    // why seat cant raise right now, None when it can
+    fn raise_blocked(&self, seat: u8) -> Option<&'static str> {
+        if self.acted.contains(&seat) {
+            Some("Betting not reopened")
+        } else if self.raise_capped() {
+            Some("Raise cap reached")
+        } else {
+            None
         }
-        // println!("refund excess done s bets {:?}", self.done_s_bets);
-        // Extract necessary data from the latest bet
-        let (latest_bet_a, latest_bet_paid, latest_bet_pp, latest_bet_value) = {
-            let latest_bet = self.done_s_bets.last().unwrap();
-            (
-                latest_bet.a,
-                latest_bet.paid.clone(),
-                latest_bet.pp.clone(),
-                self.ah.actions[latest_bet.a].value
-            )
-        };
-        // println!("refund excess called {:?} {:?} {:?} {:?}", latest_bet_a, latest_bet_paid, latest_bet_pp, latest_bet_value);
-        if latest_bet_paid.len() == 1 {
-            let player = latest_bet_paid[0];
-            if latest_bet_pp.is_empty() {
-                // refund whole bet to player
-                self.pay_from_pot(&player, &latest_bet_value);
-                // remove this action from done_s_bets
-                self.done_s_bets.pop();
-            } else {
-                // Make the highest partial paid value the new action value
-                // Refund the old value - highest partial paid to action.seat
-                let highest_pp = latest_bet_pp.iter()
-                    .max_by(|a, b| a.amt.cmp(&b.amt))
-                    .unwrap().amt;
-                // Refund excess to player
-                let excess = latest_bet_value - highest_pp;
-                self.pay_from_pot(&player, &excess);
-                // println!("refund excess pp {:?} {:?}", player, excess);
-                // Mutate the action value
-                self.ah.actions[latest_bet_a].value = highest_pp;
-    
- 
-                // Add the highest value entries to the paid vec
-                self.done_s_bets.last_mut().unwrap().paid
-                    .extend(latest_bet_pp.iter()
-                        .filter(|x| x.amt == highest_pp)
-                        .map(|x| x.seat));
-
-                // Mutate the done_s_bet last pp so that the highest value entries are removed
-                // The lower value entries are kept as is
-                self.done_s_bets.last_mut().unwrap().pp
-                    .retain(|x| x.amt != highest_pp);
+    }
+
+    // a raise of at least min_raise is a full raise and reopens the betting for everyone
+    // a smaller all in is incomplete, players that already acted can only call or fold against it
+    // incomplete all ins that add up to min_raise reopen it like a full raise would
+    //   bet 10, all in raise 5, all in raise 6  ->  11 since the bet, the bettor can raise again
+    // any bet opens the action since nobody has acted against a bet yet
+    fn track_raise(&mut self, action: &Action) {
+        match action.t {
+            ActionType::Bet | ActionType::BetAI => {
+                self.min_raise = self.min_raise.max(action.value);
+                self.acted = vec![action.seat];
+                self.short_raises = 0;
+            }
+            ActionType::Raise | ActionType::RaiseAI => {
+                self.raises += 1;
+                if action.value >= self.min_raise {
+                    self.min_raise = action.value;
+                    self.acted = vec![action.seat];
+                    self.short_raises = 0;
+                } else if self.short_raises + action.value >= self.min_raise {
+                    self.acted = vec![action.seat];
+                    self.short_raises = 0;
+                } else {
+                    self.short_raises += action.value;
+                    self.acted.push(action.seat);
+                }
             }
+            _ => self.acted.push(action.seat),
         }
     }
 
@@ -957,7 +1212,7 @@ impl Dealer {
     // should return a Vec<ActionType> of available actions for the current player
 
     pub fn get_available_actions(&self) -> Vec<ActionType> {
-        if self.stage == Stages::Showdown {
+        if !self.stage.is_betting() {
             panic!("Hand is over cant get_available_actions");
         }
         let mut available_actions: Vec<ActionType> = vec![];
@@ -973,61 +1228,152 @@ impl Dealer {
             }
         } else {
             // has bet, so you can only call fold or raise
-            available_actions.push(ActionType::Fold);
+            // a bb posted live on coming back has paid the blinds already and can check
+            if call_amt == 0 {
+                available_actions.push(ActionType::Check);
+            } else {
+                available_actions.push(ActionType::Fold);
+            }
+            // a stack at or below the call amount can only call all in, with or without the raise cap
             if call_amt >= p_chips {
                 available_actions.push(ActionType::CallAI);
             } else {
-                available_actions.push(ActionType::Call);
+                if call_amt > 0 {
+                    available_actions.push(ActionType::Call);
+                }
                 // let min raise = call_amt 
                 let active_players = self.p.iter().filter(|p| !p.is_folded && !p.is_all_in).count();
-                if active_players > 1 {
-                    // not counting the bb raise, there can be a max of 3 raises preflop
-                    // and a max of 4 raises postflop
-                    // println!("s_bets {:?} done_s_bets {:?}", self.s_bets, self.done_s_bets);
-                    let is_flop_stage = self.stage == Stages::Flop;
-                    let max_raise = 4; 
-                    let mut num_raises = 0;
-
-                    // Combine the iterations
-                    for sb in self.s_bets.iter().chain(self.done_s_bets.iter()
-                        .filter(|&sb| {
-                            // Skip specific checks for Flop stage in done_s_bets
-                            if is_flop_stage && self.ah.pf.contains(&sb.a) {
-                                return false;
-                            }
-                            true
-                        })
-                
-                    ) {
-
-                        let at = &self.ah.actions[sb.a].t;
-                        if *at == ActionType::Raise || *at == ActionType::RaiseAI {
-                            num_raises += 1;
-                        }
-                    }
-
-                    if num_raises > max_raise {
-                        if p_chips < call_amt + self.pot {
-                            available_actions.push(ActionType::RaiseAI);
-                        }
-                       // we ignore for num_raises > max_raise and too many chips to raiseAI
-                    } else if p_chips < call_amt + self.pot {
+                // only a full raise since this player last acted lets them raise again
+                // and no raises once the street has hit the cap in self.config
+                if active_players > 1 && !self.acted.contains(&self.curr) && !self.raise_capped() {
+                    if p_chips < call_amt + self.pot {
                         available_actions.push(ActionType::RaiseAI);
                     } else {
                         // cant raise if you can raiseAI
                         available_actions.push(ActionType::Raise);
                     }
-                    
-
                 }
-
             }
-            
         }
 
         // println!("available actions {:?}", available_actions);
         available_actions 
     } 
+
+    // min and max value for a Bet or Raise by the current player, the value excludes the call amount
+    // betting is pot limit, a raise can be up to the pot after calling and at least min_raise
+    pub fn get_bet_range(&self) -> (u16, u16) {
+        let call_amt = self.get_call_amt(&self.curr);
+        let p_chips = self.p.iter().find(|p| p.seat == self.curr).map(|p| p.chips).unwrap_or(0);
+        let max = std::cmp::min(call_amt + self.pot, p_chips.saturating_sub(call_amt));
+        (std::cmp::min(self.min_raise, max), max)
+    }
+
+    // checks an action against the rules p_action panics on, without changing anything
+    // the action type must also be in get_available_actions
+    pub fn check_action(&self, action: &Action) -> Result<(), DealerError> {
+        if self.stage == Stages::WaitingForHand {
+            return Err(DealerError::NoHand);
+        }
+        if !self.stage.is_betting() {
+            return Err(DealerError::HandOver);
+        }
+        if self.curr != action.seat {
+            return Err(DealerError::NotYourTurn { seat: action.seat, curr: self.curr });
+        }
+        let p_chips = match self.p.iter().find(|p| p.seat == action.seat) {
+            Some(p) => p.chips,
+            None => return Err(DealerError::SeatNotFound(action.seat)),
+        };
+        if matches!(action.t, ActionType::Raise | ActionType::RaiseAI) && self.raise_blocked(action.seat).is_some() {
+            return Err(DealerError::IllegalAction);
+        }
+        if !self.get_available_actions().contains(&action.t) {
+            return Err(DealerError::IllegalAction);
+        }
+        let call_amt = self.get_call_amt(&action.seat);
+        let valid = match action.t {
+            ActionType::Fold | ActionType::Check => action.value == 0,
+            ActionType::Call => action.value == 0 && call_amt > 0 && call_amt <= p_chips,
+            ActionType::CallAI => action.value == 0 && call_amt > 0 && call_amt >= p_chips,
+            ActionType::Bet => action.value > 0
+                && call_amt == 0
+                && action.value >= self.get_bet_range().0
+                && action.value <= self.get_bet_range().1,
+            ActionType::BetAI => call_amt == 0 && action.value == p_chips,
+            // the pot can be smaller than min_raise after a short sb, a pot sized raise is then allowed
+            ActionType::Raise => action.value >= self.get_bet_range().0
+                && action.value + call_amt <= p_chips
+                && action.value <= call_amt + self.pot,
+            ActionType::RaiseAI => action.value + call_amt == p_chips
+                && action.value <= call_amt + self.pot,
+        };
+        if !valid {
+            return Err(DealerError::InvalidValue);
+        }
+        Ok(())
+    }
+
+    pub fn try_p_action(&mut self, action: Action) -> Result<(), DealerError> {
+        self.check_action(&action)?;
+        self.p_action(action);
+        Ok(())
+    }
+
+    pub fn try_new_hand(&mut self) -> Result<(), DealerError> {
+        self.check_between_hands()?;
+        if self.seats_to_deal().len() < 2 {
+            return Err(DealerError::NotEnoughPlayers);
+        }
+        self.new_hand();
+        Ok(())
+    }
+
+    // aborts the hand before it is settled, eg a misdeal or a crash, every player gets their start_bal back
+    // the actions stay in ah for the record, the dealer goes back to WaitingForHand ready for new_hand
+    // a settled hand has been paid out and cant be voided
+    pub fn void_hand(&mut self) {
+        match self.stage {
+            Stages::WaitingForHand => panic!("void_hand No hand to void"),
+            Stages::Settled => panic!("void_hand Hand already settled"),
+            _ => {}
+        }
+        for sb in &self.ah.start_bal {
+            if let Some(player) = self.p.iter_mut().find(|p| p.seat == sb.seat) {
+                player.chips = sb.bal;
+                player.is_folded = false;
+                player.is_all_in = false;
+            }
+        }
+        self.pot = 0;
+        self.s_bets = vec![];
+        self.done_s_bets = vec![];
+        self.committed = vec![];
+        self.refunds = vec![];
+        self.advance = vec![];
+        self.ah.voided = true;
+        self.set_stage(Stages::WaitingForHand);
+        self.emit(DealerEvent::HandVoided { chips: self.ah.start_bal.clone() });
+    }
+
+    pub fn try_void_hand(&mut self) -> Result<(), DealerError> {
+        match self.stage {
+            Stages::WaitingForHand => return Err(DealerError::NoHand),
+            Stages::Settled => return Err(DealerError::AlreadySettled),
+            _ => {}
+        }
+        self.void_hand();
+        Ok(())
+    }
+
+    pub fn try_handle_showdown(&mut self) -> Result<ShowdownResult, DealerError> {
+        match self.stage {
+            Stages::AwaitingSettlement => Ok(self.handle_showdown()),
+            Stages::Settled => Err(DealerError::AlreadySettled),
+            Stages::WaitingForHand => Err(DealerError::NoHand),
+            _ => Err(DealerError::HandNotOver),
+        }
+    }
 }
 #[cfg(test)]
 mod tests {
@@ -1150,10 +1496,10 @@ mod tests {
         let done_s_bet_indexes = &dealer.done_s_bets.iter().map(|s| s.a).collect::<Vec<usize>>();
         assert_eq!(done_s_bet_indexes, &vec![0, 1, 4, 8, 9]); 
         // println!("ah {:?}", dealer.ah);
-        assert_eq!(dealer.stage, Stages::Showdown);
+        assert_eq!(dealer.stage, Stages::AwaitingSettlement);
         // // execute showdown
         dealer.handle_showdown();
-        assert_eq!(dealer.stage, Stages::Showdown);
+        assert_eq!(dealer.stage, Stages::Settled);
         assert_eq!(dealer.pot, 0);
 
 
@@ -1221,9 +1567,9 @@ mod tests {
         });
         assert_eq!(dealer.pot, 34);
         //showdown
-        assert_eq!(dealer.stage, Stages::Showdown);
+        assert_eq!(dealer.stage, Stages::AwaitingSettlement);
         dealer.handle_showdown();
-        assert_eq!(dealer.stage, Stages::Showdown);
+        assert_eq!(dealer.stage, Stages::Settled);
         
 
 
@@ -1266,9 +1612,9 @@ mod tests {
             t: ActionType::Fold,
             value: 0
         });   
-        assert_eq!(dealer.stage, Stages::Showdown);
+        assert_eq!(dealer.stage, Stages::AwaitingSettlement);
         dealer.handle_showdown();
-        assert_eq!(dealer.stage, Stages::Showdown);
+        assert_eq!(dealer.stage, Stages::Settled);
         assert_eq!(dealer.pot, 0);
 
      }
@@ -1487,9 +1833,9 @@ mod tests {
             value: 0
         });
         // showdown
-        assert_eq!(dealer.stage, Stages::Showdown);
+        assert_eq!(dealer.stage, Stages::AwaitingSettlement);
         dealer.handle_showdown();
-        assert_eq!(dealer.stage, Stages::Showdown);
+        assert_eq!(dealer.stage, Stages::Settled);
         assert_eq!(dealer.pot, 0);
 
 
@@ -1544,7 +1890,7 @@ mod tests {
             t: ActionType::Fold,
             value: 0
         });
-        assert_eq!(dealer.stage, Stages::Showdown);
+        assert_eq!(dealer.stage, Stages::AwaitingSettlement);
         dealer.handle_showdown();
         // println!("phands {:?}", dealer.p.iter().map(|p| p.hand).collect::<Vec<[Card; 4]>>());
 
@@ -1603,7 +1949,7 @@ mod tests {
             t: ActionType::Call,
             value: 0
         });
-        assert_eq!(dealer.stage, Stages::Showdown);
+        assert_eq!(dealer.stage, Stages::AwaitingSettlement);
         dealer.handle_showdown();
         // println!("phands {:?}", dealer.p.iter().map(|p| p.hand).collect::<Vec<[Card; 4]>>());
 
@@ -1661,7 +2007,7 @@ mod tests {
         println!("got here!!!!!");
         let aa = dealer.get_available_actions(); 
         println!("aa {:#?}", aa);
-        assert_eq!(dealer.stage, Stages::Showdown);
+        assert_eq!(dealer.stage, Stages::AwaitingSettlement);
         dealer.handle_showdown();
         // println!("phands {:?}", dealer.p.iter().map(|p| p.hand).collect::<Vec<[Card; 4]>>());
 
@@ -1741,10 +2087,549 @@ mod tests {
             value: 0
         });
        
-        assert_eq!(dealer.stage, Stages::Showdown);
+        assert_eq!(dealer.stage, Stages::AwaitingSettlement);
         dealer.handle_showdown();
         // println!("phands {:?}", dealer.p.iter().map(|p| p.hand).collect::<Vec<[Card; 4]>>());
 
     }
 
+    #[test]
+    fn test_heads_up() {
+        let mut dealer = Dealer::new(123, vec![
+            Player::new(1, 20),
+            Player::new(2, 20),
+        ]);
+        dealer.new_hand();
+        // the button posts the small blind and acts first preflop
+        assert_eq!(dealer.ah.start_bal[0].seat, 1);
+        assert_eq!(dealer.committed(&1), SMALL_BLIND);
+        assert_eq!(dealer.committed(&2), BIG_BLIND);
+        assert_eq!(dealer.curr, 1);
+        dealer.p_action(Action { seat: 1, t: ActionType::Call, value: 0 });
+        // the bb still has its option
+        assert_eq!(dealer.curr, 2);
+        dealer.p_action(Action { seat: 2, t: ActionType::Check, value: 0 });
+        // the bb acts first on the flop
+        assert_eq!(dealer.stage, Stages::Flop);
+        assert_eq!(dealer.curr, 2);
+        dealer.p_action(Action { seat: 2, t: ActionType::Check, value: 0 });
+        dealer.p_action(Action { seat: 1, t: ActionType::Check, value: 0 });
+        assert_eq!(dealer.stage, Stages::AwaitingSettlement);
+        dealer.handle_showdown();
+    }
+
+    #[test]
+    fn test_shrinks_to_heads_up() {
+        let mut dealer = Dealer::new(123, vec![
+            Player::new(1, 20),
+            Player::new(2, 20),
+            Player::new(3, 20),
+        ]);
+        dealer.new_hand();
+        assert_eq!(dealer.ah.start_bal[0].seat, 2);
+        dealer.p_action(Action { seat: 1, t: ActionType::Fold, value: 0 });
+        dealer.p_action(Action { seat: 2, t: ActionType::Fold, value: 0 });
+        dealer.handle_showdown();
+
+        dealer.p.retain(|p| p.seat != 3);
+        dealer.button = 2;
+        dealer.new_hand();
+        assert_eq!(dealer.ah.start_bal.iter().map(|sb| sb.seat).collect::<Vec<u8>>(), vec![2, 1]);
+        assert_eq!(dealer.curr, 2);
+        dealer.p_action(Action { seat: 2, t: ActionType::Raise, value: 2 });
+        dealer.p_action(Action { seat: 1, t: ActionType::Call, value: 0 });
+        assert_eq!(dealer.stage, Stages::Flop);
+        assert_eq!(dealer.curr, 1);
+    }
+
+    #[test]
+    fn test_second_hand() {
+        let mut dealer = Dealer::new(123, vec![
+            Player::new(1, 5),
+            Player::new(2, 5),
+            Player::new(3, 5),
+        ]);
+        dealer.new_hand();
+        dealer.p_action(Action {
+            seat: 1,
+            t: ActionType::Call,
+            value: 0
+        });
+        dealer.p_action(Action {
+            seat: 2,
+            t: ActionType::Call,
+            value: 0
+        });
+        dealer.p_action(Action {
+            seat: 3,
+            t: ActionType::Check,
+            value: 0
+        });
+        dealer.p_action(Action {
+            seat: 2,
+            t: ActionType::Check,
+            value: 0
+        });
+        dealer.p_action(Action {
+            seat: 3,
+            t: ActionType::Check,
+            value: 0
+        });
+        dealer.p_action(Action {
+            seat: 1,
+            t: ActionType::Check,
+            value: 0
+        });
+        dealer.handle_showdown();
+
+        dealer.new_hand();
+        assert_eq!(dealer.ah.actions.len(), 2);
+        assert_eq!(dealer.ah.pf, vec![0, 1]);
+        assert!(dealer.ah.f.is_empty());
+        dealer.p_action(Action {
+            seat: 1,
+            t: ActionType::Fold,
+            value: 0
+        });
+        dealer.p_action(Action {
+            seat: 2,
+            t: ActionType::Call,
+            value: 0
+        });
+        dealer.p_action(Action {
+            seat: 3,
+            t: ActionType::Check,
+            value: 0
+        });
+        assert_eq!(dealer.stage, Stages::Flop);
+        assert_eq!(dealer.ah.f.len(), 0);
+        dealer.p_action(Action {
+            seat: 2,
+            t: ActionType::Check,
+            value: 0
+        });
+        dealer.p_action(Action {
+            seat: 3,
+            t: ActionType::Check,
+            value: 0
+        });
+        assert_eq!(dealer.stage, Stages::AwaitingSettlement);
+        dealer.handle_showdown();
+        assert_eq!(dealer.p.iter().map(|p| p.chips).sum::<u16>(), 15);
+    }
+
+    #[test]
+    fn test_try_p_action() {
+        let mut dealer = Dealer::new(123, vec![
+            Player::new(1, 15),
+            Player::new(2, 12),
+            Player::new(3, 10),
+        ]);
+        assert_eq!(dealer.try_p_action(Action {
+            seat: 1,
+            t: ActionType::Call,
+            value: 0
+        }), Err(DealerError::NoHand));
+        dealer.try_new_hand().unwrap();
+        assert_eq!(dealer.try_new_hand(), Err(DealerError::HandNotOver));
+        assert_eq!(dealer.try_p_action(Action {
+            seat: 2,
+            t: ActionType::Call,
+            value: 0
+        }), Err(DealerError::NotYourTurn { seat: 2, curr: 1 }));
+        assert_eq!(dealer.try_p_action(Action {
+            seat: 1,
+            t: ActionType::Check,
+            value: 0
+        }), Err(DealerError::IllegalAction));
+        assert_eq!(dealer.try_p_action(Action {
+            seat: 1,
+            t: ActionType::Call,
+            value: 2
+        }), Err(DealerError::InvalidValue));
+        // pot is 3 so the max raise is 5
+        assert_eq!(dealer.try_p_action(Action {
+            seat: 1,
+            t: ActionType::Raise,
+            value: 6
+        }), Err(DealerError::InvalidValue));
+        assert_eq!(dealer.try_handle_showdown(), Err(DealerError::HandNotOver));
+        dealer.try_p_action(Action {
+            seat: 1,
+            t: ActionType::Raise,
+            value: 5
+        }).unwrap();
+        assert_eq!(dealer.curr, 2);
+        assert_eq!(dealer.pot, 10);
+    }
+
+    #[test]
+    fn test_get_bet_range() {
+        let mut dealer = Dealer::new(123, vec![
+            Player::new(1, 15),
+            Player::new(2, 12),
+            Player::new(3, 10),
+        ]);
+        dealer.new_hand();
+        // call 2 into a pot of 3, the smallest raise is the big blind
+        assert_eq!(dealer.get_bet_range(), (2, 5));
+        dealer.p_action(Action {
+            seat: 1,
+            t: ActionType::Raise,
+            value: 5
+        });
+        // seat 2 calls 6 with 11 chips left, and has to raise at least 5 again
+        assert_eq!(dealer.get_bet_range(), (5, 5));
+    }
+
+    #[test]
+    fn test_showdown_result() {
+        let mut dealer = Dealer::new(123, vec![
+            Player::new(1, 10),
+            Player::new(2, 3),
+            Player::new(3, 10),
+        ]);
+        dealer.new_hand();
+        for (seat, t, value) in [
+            (1, ActionType::Call, 0),
+            (2, ActionType::Call, 0),
+            (3, ActionType::Check, 0),
+            (2, ActionType::BetAI, 1),
+            (3, ActionType::Raise, 2),
+            (1, ActionType::Call, 0),
+        ] {
+            dealer.try_p_action(Action { seat, t, value }).unwrap();
+        }
+        assert_eq!(dealer.stage, Stages::AwaitingSettlement);
+        let result = dealer.handle_showdown();
+        assert_eq!(result.pots.len(), 2);
+        assert_eq!((result.pots[0].value, result.pots[0].eligible.clone()), (9, vec![2, 3, 1]));
+        assert_eq!((result.pots[1].value, result.pots[1].eligible.clone()), (4, vec![3, 1]));
+        for pot in &result.pots {
+            assert_eq!(pot.awards.iter().map(|(_, amt)| amt).sum::<u16>(), pot.value);
+            assert_eq!(pot.equities.len(), pot.eligible.len());
+        }
+        assert_eq!(result.awarded.iter().map(|(_, amt)| amt).sum::<u16>(), 13);
+        assert!(result.refunds.is_empty());
+        for (seat, amt) in &result.awarded {
+            let start = dealer.ah.start_bal.iter().find(|sb| sb.seat == *seat).unwrap().bal;
+            let spent = if *seat == 2 { 3 } else { 5 };
+            assert_eq!(dealer.p.iter().find(|p| p.seat == *seat).unwrap().chips, start - spent + amt);
+        }
+    }
+
+    #[test]
+    fn test_showdown_result_refund() {
+        let mut dealer = Dealer::new(123, vec![
+            Player::new(1, 5),
+            Player::new(2, 5),
+            Player::new(3, 5),
+        ]);
+        dealer.new_hand();
+        dealer.p_action(Action {
+            seat: 1,
+            t: ActionType::Fold,
+            value: 0
+        });
+        dealer.p_action(Action {
+            seat: 2,
+            t: ActionType::Fold,
+            value: 0
+        });
+        let result = dealer.handle_showdown();
+        assert_eq!(result.refunds, vec![(3, 1)]);
+        assert_eq!(result.awarded, vec![(3, 2)]);
+        assert_eq!(result.pots[0].eligible, vec![3]);
+    }
+
+    #[test]
+    fn test_lifecycle() {
+        let mut dealer = Dealer::new(123, vec![
+            Player::new(1, 5),
+            Player::new(2, 5),
+        ]);
+        assert_eq!(dealer.stage, Stages::WaitingForHand);
+        assert_eq!(dealer.try_handle_showdown(), Err(DealerError::NoHand));
+        dealer.new_hand();
+        assert_eq!(dealer.stage, Stages::PreFlop);
+        assert_eq!(dealer.try_handle_showdown(), Err(DealerError::HandNotOver));
+        dealer.p_action(Action {
+            seat: 1,
+            t: ActionType::Fold,
+            value: 0
+        });
+        assert_eq!(dealer.stage, Stages::AwaitingSettlement);
+        assert_eq!(dealer.try_new_hand(), Err(DealerError::NotSettled));
+        assert_eq!(dealer.try_p_action(Action {
+            seat: 1,
+            t: ActionType::Check,
+            value: 0
+        }), Err(DealerError::HandOver));
+        dealer.try_handle_showdown().unwrap();
+        assert_eq!(dealer.stage, Stages::Settled);
+        assert_eq!(dealer.try_handle_showdown(), Err(DealerError::AlreadySettled));
+        dealer.try_new_hand().unwrap();
+        assert_eq!(dealer.stage, Stages::PreFlop);
+    }
+
+    #[test]
+    #[should_panic(expected = "showdown Hand already settled")]
+    fn test_showdown_twice() {
+        let mut dealer = Dealer::new(123, vec![
+            Player::new(1, 5),
+            Player::new(2, 5),
+        ]);
+        dealer.new_hand();
+        dealer.p_action(Action {
+            seat: 1,
+            t: ActionType::Fold,
+            value: 0
+        });
+        dealer.handle_showdown();
+        dealer.handle_showdown();
+    }
+
+    #[test]
+    fn test_void_settled_hand() {
+        let mut dealer = Dealer::new(123, vec![
+            Player::new(1, 10),
+            Player::new(2, 10),
+        ]);
+        dealer.new_hand();
+        dealer.p_action(Action { seat: 1, t: ActionType::Fold, value: 0 });
+        dealer.handle_showdown();
+        let chips = dealer.p.iter().map(|p| p.chips).collect::<Vec<u16>>();
+        assert_eq!(dealer.try_void_hand(), Err(DealerError::AlreadySettled));
+        assert_eq!(dealer.stage, Stages::Settled);
+        assert!(!dealer.ah.voided);
+        assert_eq!(dealer.p.iter().map(|p| p.chips).collect::<Vec<u16>>(), chips);
+    }
+
+    #[test]
+    #[should_panic(expected = "void_hand Hand already settled")]
+    fn test_void_hand_after_showdown() {
+        let mut dealer = Dealer::new(123, vec![
+            Player::new(1, 10),
+            Player::new(2, 10),
+        ]);
+        dealer.new_hand();
+        dealer.p_action(Action { seat: 1, t: ActionType::Fold, value: 0 });
+        dealer.handle_showdown();
+        dealer.void_hand();
+    }
+
+    #[test]
+    fn test_new_deck_each_hand() {
+        let players = || vec![Player::new(1, 50), Player::new(2, 50)];
+        let mut dealer = Dealer::new(123, players());
+        dealer.new_hand();
+        let first = dealer.p.iter().map(|p| p.hand).collect::<Vec<_>>();
+        assert_eq!(dealer.hand_seed(), 123);
+        dealer.p_action(Action { seat: 1, t: ActionType::Fold, value: 0 });
+        dealer.handle_showdown();
+        dealer.new_hand();
+        assert_eq!(dealer.hand_seed(), 124);
+        assert_ne!(dealer.p.iter().map(|p| p.hand).collect::<Vec<_>>(), first);
+
+        // the same seed deals the same hands again
+        let mut replay = Dealer::new(123, players());
+        replay.new_hand();
+        assert_eq!(replay.p.iter().map(|p| p.hand).collect::<Vec<_>>(), first);
+    }
+
+    #[test]
+    fn test_void_hand() {
+        let mut dealer = Dealer::new(123, vec![
+            Player::new(1, 10),
+            Player::new(2, 7),
+            Player::new(3, 5),
+        ]);
+        assert_eq!(dealer.try_void_hand(), Err(DealerError::NoHand));
+        dealer.new_hand();
+        dealer.p_action(Action {
+            seat: 1,
+            t: ActionType::Call,
+            value: 0
+        });
+        dealer.p_action(Action {
+            seat: 2,
+            t: ActionType::Fold,
+            value: 0
+        });
+        dealer.drain_events();
+        dealer.void_hand();
+        assert_eq!(dealer.stage, Stages::WaitingForHand);
+        assert_eq!(dealer.pot, 0);
+        assert!(dealer.s_bets.is_empty() && dealer.done_s_bets.is_empty());
+        assert!(dealer.ah.voided);
+        assert_eq!(dealer.ah.actions.len(), 4);
+        for (seat, chips) in [(1, 10), (2, 7), (3, 5)] {
+            let p = dealer.p.iter().find(|p| p.seat == seat).unwrap();
+            assert_eq!(p.chips, chips);
+            assert!(!p.is_folded);
+        }
+        assert_eq!(dealer.drain_events().last(), Some(&DealerEvent::HandVoided { chips: dealer.ah.start_bal.clone() }));
+
+        // ready for the next hand, a hand over but not yet settled can be voided too
+        dealer.new_hand();
+        assert!(!dealer.ah.voided);
+        dealer.p_action(Action {
+            seat: 1,
+            t: ActionType::Fold,
+            value: 0
+        });
+        dealer.p_action(Action {
+            seat: 2,
+            t: ActionType::Fold,
+            value: 0
+        });
+        assert_eq!(dealer.stage, Stages::AwaitingSettlement);
+        dealer.void_hand();
+        assert_eq!(dealer.p.iter().map(|p| p.chips).sum::<u16>(), 22);
+        assert_eq!(dealer.p.iter().find(|p| p.seat == 3).unwrap().chips, 5);
+    }
+
+    #[test]
+    fn test_short_all_in_3handed() {
+        let mut dealer = Dealer::new(123, vec![
+            Player::new(1, 30),
+            Player::new(2, 9),
+            Player::new(3, 30),
+        ]);
+        dealer.new_hand();
+        dealer.try_p_action(Action { seat: 1, t: ActionType::Raise, value: 4 }).unwrap();
+        assert_eq!(dealer.min_raise, 4);
+        // seat 2 can only put in 3 more than the call, less than a full raise
+        assert_eq!(dealer.get_available_actions(), vec![ActionType::Fold, ActionType::Call, ActionType::RaiseAI]);
+        dealer.try_p_action(Action { seat: 2, t: ActionType::RaiseAI, value: 3 }).unwrap();
+        assert_eq!(dealer.min_raise, 4);
+        // the big blind has not acted yet so it can still raise
+        assert!(dealer.get_available_actions().contains(&ActionType::Raise));
+        dealer.try_p_action(Action { seat: 3, t: ActionType::Call, value: 0 }).unwrap();
+        // seat 1 already acted and the all in was not a full raise
+        assert_eq!(dealer.get_available_actions(), vec![ActionType::Fold, ActionType::Call]);
+        assert_eq!(dealer.try_p_action(Action { seat: 1, t: ActionType::Raise, value: 4 }), Err(DealerError::IllegalAction));
+        dealer.try_p_action(Action { seat: 1, t: ActionType::Call, value: 0 }).unwrap();
+        assert_eq!(dealer.stage, Stages::Flop);
+        assert_eq!(dealer.pot, 27);
+        // new street, back to the big blind
+        assert_eq!(dealer.min_raise, BIG_BLIND);
+        assert!(dealer.acted.is_empty());
+    }
+
+    #[test]
+    #[should_panic(expected = "Betting not reopened")]
+    fn test_raise_after_short_all_in() {
+        let mut dealer = Dealer::new(123, vec![
+            Player::new(1, 30),
+            Player::new(2, 9),
+            Player::new(3, 30),
+        ]);
+        dealer.new_hand();
+        dealer.p_action(Action { seat: 1, t: ActionType::Raise, value: 4 });
+        dealer.p_action(Action { seat: 2, t: ActionType::RaiseAI, value: 3 });
+        dealer.p_action(Action { seat: 3, t: ActionType::Call, value: 0 });
+        dealer.p_action(Action { seat: 1, t: ActionType::Raise, value: 4 });
+    }
+
+    #[test]
+    fn test_short_all_ins_add_up() {
+        let mut dealer = Dealer::new(123, vec![
+            Player::new(1, 100),
+            Player::new(2, 100),
+            Player::new(3, 100),
+            Player::new(4, 17),
+            Player::new(5, 23),
+        ]);
+        dealer.new_hand();
+        for (seat, t) in [(4, ActionType::Call), (5, ActionType::Call), (1, ActionType::Call), (2, ActionType::Call), (3, ActionType::Check)] {
+            dealer.try_p_action(Action { seat, t, value: 0 }).unwrap();
+        }
+        assert_eq!(dealer.stage, Stages::Flop);
+        dealer.try_p_action(Action { seat: 2, t: ActionType::Bet, value: 10 }).unwrap();
+        dealer.try_p_action(Action { seat: 3, t: ActionType::Call, value: 0 }).unwrap();
+        // 5 over the bet is short of a full raise
+        dealer.try_p_action(Action { seat: 4, t: ActionType::RaiseAI, value: 5 }).unwrap();
+        assert_eq!(dealer.acted, vec![2, 3, 4]);
+        assert_eq!(dealer.short_raises, 5);
+        // another 6 makes 11 since the bet, a full raise between them
+        dealer.try_p_action(Action { seat: 5, t: ActionType::RaiseAI, value: 6 }).unwrap();
+        assert_eq!(dealer.acted, vec![5]);
+        assert_eq!(dealer.min_raise, 10);
+        dealer.try_p_action(Action { seat: 1, t: ActionType::Call, value: 0 }).unwrap();
+        // the bettor and the caller can raise again
+        assert_eq!(dealer.curr, 2);
+        assert!(dealer.get_available_actions().iter().any(|t| matches!(t, ActionType::Raise | ActionType::RaiseAI)));
+        dealer.try_p_action(Action { seat: 2, t: ActionType::RaiseAI, value: dealer.p[1].chips - dealer.get_call_amt(&2) }).unwrap();
+    }
+
+    #[test]
+    fn test_blocked_raise_changes_nothing() {
+        let mut dealer = Dealer::new(123, vec![
+            Player::new(1, 30),
+            Player::new(2, 9),
+            Player::new(3, 30),
+        ]);
+        dealer.new_hand();
+        dealer.p_action(Action { seat: 1, t: ActionType::Raise, value: 4 });
+        dealer.p_action(Action { seat: 2, t: ActionType::RaiseAI, value: 3 });
+        dealer.p_action(Action { seat: 3, t: ActionType::Call, value: 0 });
+        let before = dealer.clone();
+        let raise = Action { seat: 1, t: ActionType::Raise, value: 4 };
+        assert_eq!(dealer.check_action(&raise), Err(DealerError::IllegalAction));
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| dealer.p_action(raise)));
+        assert!(result.is_err());
+        assert_eq!(dealer.p.iter().map(|p| p.chips).collect::<Vec<u16>>(), before.p.iter().map(|p| p.chips).collect::<Vec<u16>>());
+        assert_eq!((dealer.pot, dealer.raises, &dealer.acted), (before.pot, before.raises, &before.acted));
+        assert_eq!(dealer.ah.actions.len(), before.ah.actions.len());
+    }
+
+    #[test]
+    fn test_short_all_in_4handed() {
+        let mut dealer = Dealer::new(123, vec![
+            Player::new(1, 40),
+            Player::new(2, 40),
+            Player::new(3, 40),
+            Player::new(4, 14),
+        ]);
+        dealer.new_hand();
+        for (seat, t, value) in [
+            (4, ActionType::Raise, 4),
+            (1, ActionType::Call, 0),
+            (2, ActionType::Call, 0),
+            // a full raise reopens the betting for everyone
+            (3, ActionType::Raise, 6),
+        ] {
+            dealer.try_p_action(Action { seat, t, value }).unwrap();
+        }
+        assert_eq!(dealer.min_raise, 6);
+        // too small to raise the full 6
+        assert_eq!(dealer.try_p_action(Action { seat: 4, t: ActionType::Raise, value: 2 }), Err(DealerError::IllegalAction));
+        dealer.try_p_action(Action { seat: 4, t: ActionType::RaiseAI, value: 2 }).unwrap();
+        // seats 1 and 2 have not acted since seat 3's full raise, both are short enough to only raise all in
+        assert_eq!(dealer.get_available_actions(), vec![ActionType::Fold, ActionType::Call, ActionType::RaiseAI]);
+        assert_eq!(dealer.get_bet_range().0, 6);
+        dealer.try_p_action(Action { seat: 1, t: ActionType::Call, value: 0 }).unwrap();
+        assert_eq!(dealer.get_available_actions(), vec![ActionType::Fold, ActionType::Call, ActionType::RaiseAI]);
+        dealer.try_p_action(Action { seat: 2, t: ActionType::Call, value: 0 }).unwrap();
+        // seat 3 made the last full raise, the short all in does not reopen it
+        assert_eq!(dealer.curr, 3);
+        assert_eq!(dealer.get_available_actions(), vec![ActionType::Fold, ActionType::Call]);
+        dealer.try_p_action(Action { seat: 3, t: ActionType::Call, value: 0 }).unwrap();
+        assert_eq!(dealer.stage, Stages::Flop);
+        assert_eq!(dealer.pot, 56);
+    }
+
+    #[test]
+    fn test_try_new_hand_no_chips() {
+        let mut dealer = Dealer::new(123, vec![
+            Player::new(1, 15),
+            Player::new(2, 0),
+        ]);
+        // seat 2 is busted and would be sat out
+        assert_eq!(dealer.try_new_hand(), Err(DealerError::NotEnoughPlayers));
+        let mut dealer = Dealer::new(123, vec![Player::new(1, 15)]);
+        assert_eq!(dealer.try_new_hand(), Err(DealerError::NotEnoughPlayers));
+    }
+
 }
@@ -75,6 +75,26 @@ impl Deck {
         card
     }
 }
+
+// card as a 2 char string, value then suit, eg As Td 9h
+// the default card used before dealing is --
+pub fn card_to_string(card: &Card) -> String {
+    let value = match card.value {
+        1 => "A".to_string(),
+        10 => "T".to_string(),
+        11 => "J".to_string(),
+        12 => "Q".to_string(),
+        13 => "K".to_string(),
+        2..=9 => card.value.to_string(),
+        _ => return "--".to_string(),
+    };
+    let suit = match [Suit::S, Suit::H, Suit::C, Suit::D].iter().position(|s| *s == card.suit) {
+        Some(i) => ["s", "h", "c", "d"][i],
+        None => return "--".to_string(),
+    };
+    format!("{}{}", value, suit)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -93,5 +113,13 @@ mod tests {
 
         assert_ne!(deck1.cards, deck2.cards);
     }
+
+    #[test]
+    fn test_card_to_string() {
+        assert_eq!(card_to_string(&Card { value: 1, suit: Suit::S }), "As");
+        assert_eq!(card_to_string(&Card { value: 10, suit: Suit::D }), "Td");
+        assert_eq!(card_to_string(&Card { value: 7, suit: Suit::H }), "7h");
+        assert_eq!(card_to_string(&Card::default()), "--");
+    }
 }
 
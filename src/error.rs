@@ -0,0 +1,44 @@
+use std::fmt;
+
+// errors returned by the try_ versions of the dealer functions
+// the plain versions keep panicking on the same conditions
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DealerError {
+    NotYourTurn { seat: u8, curr: u8 },
+    NoHand,
+    HandOver,
+    HandNotOver,
+    NotSettled,
+    AlreadySettled,
+    IllegalAction,
+    InvalidValue,
+    SeatNotFound(u8),
+    SeatTaken(u8),
+    BuyInOutOfRange { min: u16, max: u16 },
+    NotEnoughChips(u8),
+    NotEnoughPlayers,
+    InvalidConfig(&'static str),
+}
+
+impl fmt::Display for DealerError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            DealerError::NotYourTurn { seat, curr } => write!(f, "not seat {} turn, curr is {}", seat, curr),
+            DealerError::NoHand => write!(f, "no hand has been dealt"),
+            DealerError::HandOver => write!(f, "hand is over"),
+            DealerError::HandNotOver => write!(f, "hand is not over"),
+            DealerError::NotSettled => write!(f, "hand is over but not settled"),
+            DealerError::AlreadySettled => write!(f, "hand is already settled"),
+            DealerError::IllegalAction => write!(f, "action not available"),
+            DealerError::InvalidValue => write!(f, "invalid action value"),
+            DealerError::SeatNotFound(seat) => write!(f, "seat {} not found", seat),
+            DealerError::SeatTaken(seat) => write!(f, "seat {} is taken", seat),
+            DealerError::BuyInOutOfRange { min, max } => write!(f, "stack must be between {} and {}", min, max),
+            DealerError::NotEnoughChips(seat) => write!(f, "seat {} does not have enough chips", seat),
+            DealerError::NotEnoughPlayers => write!(f, "need at least 2 players"),
+            DealerError::InvalidConfig(field) => write!(f, "invalid table config {}", field),
+        }
+    }
+}
+
+impl std::error::Error for DealerError {}
@@ -0,0 +1,123 @@
+use rs_handstrength::Card;
+use crate::dealer::*;
+
+// typed events the dealer queues as the hand goes, so uis, loggers and network layers
+// can react to what changed instead of diffing the Dealer after every p_action
+// the queue is drained with drain_events, anything left over from the last hand is dropped by new_hand
+// CardsDealt has the hole cards, use the views in view.rs for anything sent to players
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum DealerEvent {
+    // seats and chips in hand order, the small blind first
+    HandStarted { button: u8, seats: Vec<StartingBal> },
+    CardsDealt { seat: u8, hand: [Card; 4] },
+    BlindPosted { seat: u8, amt: u16 },
+    // posted after the blinds, counts toward the seats contribution to the pot
+    AntePosted { seat: u8, amt: u16 },
+    // amt is the chips the action put into the pot
+    ActionTaken { seat: u8, t: ActionType, value: u16, amt: u16 },
+    // an uncalled bet given back when the street closed
+    BetReturned { seat: u8, amt: u16 },
+    StreetChanged { stage: Stages },
+    FlopDealt { flop: [Card; 3] },
+    // poll_clock acted for the seat, the ActionTaken follows
+    TimedOut { seat: u8 },
+    // the seat's advance action no longer fits the bets, it has to act itself
+    AdvanceCancelled { seat: u8 },
+    // taken out of the pot before it is paid out
+    RakeTaken { amt: u16 },
+    // pots the showdown pays out, main pot first
+    SidePotsFormed { pots: Vec<SidePot> },
+    PotAwarded { seat: u8, amt: u16 },
+    // chips after the showdown in hand order
+    HandEnded { chips: Vec<StartingBal> },
+    // void_hand gave everyone back their starting chips
+    HandVoided { chips: Vec<StartingBal> },
+}
+
+impl Dealer {
+    pub(crate) fn emit(&mut self, event: DealerEvent) {
+        self.events.push(event);
+    }
+
+    // takes every event queued since the last drain, oldest first
+    pub fn drain_events(&mut self) -> Vec<DealerEvent> {
+        std::mem::take(&mut self.events)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::player::*;
+
+    #[test]
+    fn test_events() {
+        let mut dealer = Dealer::new(123, vec![
+            Player::new(1, 5),
+            Player::new(2, 5),
+            Player::new(3, 5),
+        ]);
+        dealer.new_hand();
+        let events = dealer.drain_events();
+        assert_eq!(events[0], DealerEvent::HandStarted {
+            button: 1,
+            seats: vec![
+                StartingBal { seat: 2, bal: 5 },
+                StartingBal { seat: 3, bal: 5 },
+                StartingBal { seat: 1, bal: 5 },
+            ]
+        });
+        assert_eq!(events.iter().filter(|e| matches!(e, DealerEvent::CardsDealt { .. })).count(), 3);
+        assert!(events.contains(&DealerEvent::StreetChanged { stage: Stages::PreFlop }));
+        assert!(events.contains(&DealerEvent::BlindPosted { seat: 2, amt: 1 }));
+        assert!(events.contains(&DealerEvent::BlindPosted { seat: 3, amt: 2 }));
+        assert!(dealer.drain_events().is_empty());
+
+        dealer.p_action(Action {
+            seat: 1,
+            t: ActionType::Fold,
+            value: 0
+        });
+        dealer.p_action(Action {
+            seat: 2,
+            t: ActionType::Call,
+            value: 0
+        });
+        dealer.p_action(Action {
+            seat: 3,
+            t: ActionType::Check,
+            value: 0
+        });
+        let events = dealer.drain_events();
+        assert_eq!(events[0], DealerEvent::ActionTaken { seat: 1, t: ActionType::Fold, value: 0, amt: 0 });
+        assert_eq!(events[1], DealerEvent::ActionTaken { seat: 2, t: ActionType::Call, value: 0, amt: 1 });
+        assert_eq!(events[2], DealerEvent::ActionTaken { seat: 3, t: ActionType::Check, value: 0, amt: 0 });
+        assert_eq!(events[3], DealerEvent::FlopDealt { flop: dealer.flop });
+        assert_eq!(events[4], DealerEvent::StreetChanged { stage: Stages::Flop });
+
+        dealer.p_action(Action {
+            seat: 2,
+            t: ActionType::Bet,
+            value: 2
+        });
+        dealer.p_action(Action {
+            seat: 3,
+            t: ActionType::Fold,
+            value: 0
+        });
+        dealer.handle_showdown();
+        let events = dealer.drain_events();
+        // the uncalled bet comes back as the street closes
+        assert_eq!(events[2], DealerEvent::BetReturned { seat: 2, amt: 2 });
+        assert_eq!(events[3], DealerEvent::StreetChanged { stage: Stages::AwaitingSettlement });
+        assert!(events.contains(&DealerEvent::PotAwarded { seat: 2, amt: 4 }));
+        assert_eq!(events.last(), Some(&DealerEvent::HandEnded {
+            chips: vec![
+                StartingBal { seat: 2, bal: 7 },
+                StartingBal { seat: 3, bal: 3 },
+                StartingBal { seat: 1, bal: 5 },
+            ]
+        }));
+    }
+}
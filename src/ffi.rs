@@ -0,0 +1,325 @@
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::ptr;
+
+use crate::dealer::*;
+use crate::error::*;
+use crate::player::*;
+
+// C api, built with the ffi feature, header is include/rs_floppoker.h
+// the manifest only builds the rlib, build the C library with
+//   cargo rustc --release --lib --features ffi --crate-type cdylib    (or staticlib)
+// build.rs writes a fresh header to OUT_DIR, copy it over include/rs_floppoker.h when the api changes
+// or run cbindgen --config cbindgen.toml --output include/rs_floppoker.h
+// the dealer is an opaque handle from fp_dealer_new that must be freed with fp_dealer_free
+// every function returns a FpStatus, nothing panics across the boundary, panics come back as FP_PANIC
+
+pub struct FpDealer {
+    dealer: Dealer,
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FpStatus {
+    Ok = 0,
+    NullPointer = 1,
+    NotYourTurn = 2,
+    HandOver = 3,
+    HandNotOver = 4,
+    IllegalAction = 5,
+    InvalidValue = 6,
+    SeatNotFound = 7,
+    NotEnoughChips = 8,
+    NotEnoughPlayers = 9,
+    BufferTooSmall = 10,
+    NoHand = 11,
+    NotSettled = 12,
+    AlreadySettled = 13,
+    SeatTaken = 14,
+    BuyInOutOfRange = 15,
+    UnknownActionType = 16,
+    InvalidConfig = 17,
+    Panic = 99,
+}
+
+impl From<DealerError> for FpStatus {
+    fn from(e: DealerError) -> Self {
+        match e {
+            DealerError::NotYourTurn { .. } => FpStatus::NotYourTurn,
+            DealerError::NoHand => FpStatus::NoHand,
+            DealerError::NotSettled => FpStatus::NotSettled,
+            DealerError::AlreadySettled => FpStatus::AlreadySettled,
+            DealerError::HandOver => FpStatus::HandOver,
+            DealerError::HandNotOver => FpStatus::HandNotOver,
+            DealerError::IllegalAction => FpStatus::IllegalAction,
+            DealerError::InvalidValue => FpStatus::InvalidValue,
+            DealerError::SeatNotFound(_) => FpStatus::SeatNotFound,
+            DealerError::SeatTaken(_) => FpStatus::SeatTaken,
+            DealerError::BuyInOutOfRange { .. } => FpStatus::BuyInOutOfRange,
+            DealerError::NotEnoughChips(_) => FpStatus::NotEnoughChips,
+            DealerError::NotEnoughPlayers => FpStatus::NotEnoughPlayers,
+            DealerError::InvalidConfig(_) => FpStatus::InvalidConfig,
+        }
+    }
+}
+
+// same order as ActionType
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FpActionType {
+    Fold = 0,
+    Check = 1,
+    Call = 2,
+    CallAI = 3,
+    Bet = 4,
+    BetAI = 5,
+    Raise = 6,
+    RaiseAI = 7,
+}
+
+impl From<FpActionType> for ActionType {
+    fn from(t: FpActionType) -> Self {
+        match t {
+            FpActionType::Fold => ActionType::Fold,
+            FpActionType::Check => ActionType::Check,
+            FpActionType::Call => ActionType::Call,
+            FpActionType::CallAI => ActionType::CallAI,
+            FpActionType::Bet => ActionType::Bet,
+            FpActionType::BetAI => ActionType::BetAI,
+            FpActionType::Raise => ActionType::Raise,
+            FpActionType::RaiseAI => ActionType::RaiseAI,
+        }
+    }
+}
+
+// action types come from C as plain integers, an enum out of range would be undefined behaviour
+impl TryFrom<u8> for FpActionType {
+    type Error = FpStatus;
+
+    fn try_from(t: u8) -> Result<Self, FpStatus> {
+        match t {
+            0 => Ok(FpActionType::Fold),
+            1 => Ok(FpActionType::Check),
+            2 => Ok(FpActionType::Call),
+            3 => Ok(FpActionType::CallAI),
+            4 => Ok(FpActionType::Bet),
+            5 => Ok(FpActionType::BetAI),
+            6 => Ok(FpActionType::Raise),
+            7 => Ok(FpActionType::RaiseAI),
+            _ => Err(FpStatus::UnknownActionType),
+        }
+    }
+}
+
+impl From<&ActionType> for FpActionType {
+    fn from(t: &ActionType) -> Self {
+        match t {
+            ActionType::Fold => FpActionType::Fold,
+            ActionType::Check => FpActionType::Check,
+            ActionType::Call => FpActionType::Call,
+            ActionType::CallAI => FpActionType::CallAI,
+            ActionType::Bet => FpActionType::Bet,
+            ActionType::BetAI => FpActionType::BetAI,
+            ActionType::Raise => FpActionType::Raise,
+            ActionType::RaiseAI => FpActionType::RaiseAI,
+        }
+    }
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FpStage {
+    WaitingForHand = 0,
+    PreFlop = 1,
+    Flop = 2,
+    AwaitingSettlement = 3,
+    Settled = 4,
+}
+
+// runs f against the dealer behind the handle, turning null handles and panics into status codes
+fn with_dealer<F>(handle: *mut FpDealer, f: F) -> FpStatus
+where
+    F: FnOnce(&mut Dealer) -> Result<(), FpStatus>,
+{
+    if handle.is_null() {
+        return FpStatus::NullPointer;
+    }
+    let handle = unsafe { &mut *handle };
+    match catch_unwind(AssertUnwindSafe(|| f(&mut handle.dealer))) {
+        Ok(Ok(())) => FpStatus::Ok,
+        Ok(Err(status)) => status,
+        Err(_) => FpStatus::Panic,
+    }
+}
+
+/// Creates a dealer for num_players players, seats and chips are arrays of num_players.
+/// Returns null if the arguments are invalid. Free the handle with fp_dealer_free.
+///
+/// # Safety
+/// seats and chips must point to at least num_players values.
+#[no_mangle]
+pub unsafe extern "C" fn fp_dealer_new(seed: u64, seats: *const u8, chips: *const u16, num_players: usize) -> *mut FpDealer {
+    if seats.is_null() || chips.is_null() || num_players < 2 {
+        return ptr::null_mut();
+    }
+    let seats = std::slice::from_raw_parts(seats, num_players);
+    let chips = std::slice::from_raw_parts(chips, num_players);
+    let p = seats.iter().zip(chips.iter()).map(|(s, c)| Player::new(*s, *c)).collect::<Vec<Player>>();
+    Box::into_raw(Box::new(FpDealer { dealer: Dealer::new(seed, p) }))
+}
+
+/// Frees a dealer from fp_dealer_new, null is ignored.
+///
+/// # Safety
+/// handle must come from fp_dealer_new and not be used after this call.
+#[no_mangle]
+pub unsafe extern "C" fn fp_dealer_free(handle: *mut FpDealer) {
+    if !handle.is_null() {
+        drop(Box::from_raw(handle));
+    }
+}
+
+/// Deals a new hand and posts the blinds.
+///
+/// # Safety
+/// handle must be null or come from fp_dealer_new.
+#[no_mangle]
+pub unsafe extern "C" fn fp_new_hand(handle: *mut FpDealer) -> FpStatus {
+    with_dealer(handle, |d| d.try_new_hand().map_err(FpStatus::from))
+}
+
+/// Applies an action for seat, t is a FpActionType value, value is 0 for fold, check and calls.
+/// Returns FP_STATUS_UNKNOWN_ACTION_TYPE if t is not a FpActionType.
+///
+/// # Safety
+/// handle must be null or come from fp_dealer_new.
+#[no_mangle]
+pub unsafe extern "C" fn fp_act(handle: *mut FpDealer, seat: u8, t: u8, value: u16) -> FpStatus {
+    with_dealer(handle, |d| {
+        let t = FpActionType::try_from(t)?;
+        d.try_p_action(Action { seat, t: t.into(), value }).map_err(FpStatus::from)
+    })
+}
+
+/// Writes the current player's available actions into out and the count into out_len.
+///
+/// # Safety
+/// handle must be null or come from fp_dealer_new, out must have room for cap values.
+#[no_mangle]
+pub unsafe extern "C" fn fp_legal_actions(handle: *mut FpDealer, out: *mut FpActionType, cap: usize, out_len: *mut usize) -> FpStatus {
+    if out.is_null() || out_len.is_null() {
+        return FpStatus::NullPointer;
+    }
+    with_dealer(handle, |d| {
+        if !d.stage.is_betting() {
+            return Err(FpStatus::HandOver);
+        }
+        let actions = d.get_available_actions();
+        *out_len = actions.len();
+        if actions.len() > cap {
+            return Err(FpStatus::BufferTooSmall);
+        }
+        for (i, t) in actions.iter().enumerate() {
+            *out.add(i) = t.into();
+        }
+        Ok(())
+    })
+}
+
+/// Pays out the pot once the hand has reached showdown.
+///
+/// # Safety
+/// handle must be null or come from fp_dealer_new.
+#[no_mangle]
+pub unsafe extern "C" fn fp_showdown(handle: *mut FpDealer) -> FpStatus {
+    with_dealer(handle, |d| d.try_handle_showdown().map(|_| ()).map_err(FpStatus::from))
+}
+
+/// Writes the stage, the seat to act and the pot.
+///
+/// # Safety
+/// handle must be null or come from fp_dealer_new, the out pointers must be valid.
+#[no_mangle]
+pub unsafe extern "C" fn fp_state(handle: *mut FpDealer, stage: *mut FpStage, curr: *mut u8, pot: *mut u16) -> FpStatus {
+    if stage.is_null() || curr.is_null() || pot.is_null() {
+        return FpStatus::NullPointer;
+    }
+    with_dealer(handle, |d| {
+        *stage = match d.stage {
+            Stages::WaitingForHand => FpStage::WaitingForHand,
+            Stages::PreFlop => FpStage::PreFlop,
+            Stages::Flop => FpStage::Flop,
+            Stages::AwaitingSettlement => FpStage::AwaitingSettlement,
+            Stages::Settled => FpStage::Settled,
+        };
+        *curr = d.curr;
+        *pot = d.pot;
+        Ok(())
+    })
+}
+
+/// Writes a seat's chips and the amount it has to call.
+///
+/// # Safety
+/// handle must be null or come from fp_dealer_new, the out pointers must be valid.
+#[no_mangle]
+pub unsafe extern "C" fn fp_player(handle: *mut FpDealer, seat: u8, chips: *mut u16, call_amt: *mut u16) -> FpStatus {
+    if chips.is_null() || call_amt.is_null() {
+        return FpStatus::NullPointer;
+    }
+    with_dealer(handle, |d| {
+        let p = d.p.iter().find(|p| p.seat == seat).ok_or(FpStatus::SeatNotFound)?;
+        *chips = p.chips;
+        *call_amt = d.get_call_amt(&seat);
+        Ok(())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ffi_hand() {
+        let seats = [1_u8, 2, 3];
+        let chips = [15_u16, 12, 10];
+        unsafe {
+            let d = fp_dealer_new(123, seats.as_ptr(), chips.as_ptr(), 3);
+            assert!(!d.is_null());
+            assert_eq!(fp_act(d, 1, FpActionType::Call as u8, 0), FpStatus::NoHand);
+            assert_eq!(fp_new_hand(d), FpStatus::Ok);
+
+            let mut out = [FpActionType::Fold; 8];
+            let mut len = 0;
+            assert_eq!(fp_legal_actions(d, out.as_mut_ptr(), 8, &mut len), FpStatus::Ok);
+            assert_eq!(&out[..len], &[FpActionType::Fold, FpActionType::Call, FpActionType::Raise]);
+            assert_eq!(fp_legal_actions(d, out.as_mut_ptr(), 1, &mut len), FpStatus::BufferTooSmall);
+
+            assert_eq!(fp_act(d, 2, FpActionType::Call as u8, 0), FpStatus::NotYourTurn);
+            assert_eq!(fp_act(d, 1, 8, 0), FpStatus::UnknownActionType);
+            assert_eq!(fp_act(d, 1, 255, 0), FpStatus::UnknownActionType);
+            assert_eq!(fp_act(d, 1, FpActionType::Fold as u8, 0), FpStatus::Ok);
+            assert_eq!(fp_act(d, 2, FpActionType::Fold as u8, 0), FpStatus::Ok);
+
+            let (mut stage, mut curr, mut pot) = (FpStage::PreFlop, 0, 0);
+            assert_eq!(fp_state(d, &mut stage, &mut curr, &mut pot), FpStatus::Ok);
+            assert_eq!(stage, FpStage::AwaitingSettlement);
+            assert_eq!(fp_showdown(d), FpStatus::Ok);
+            assert_eq!(fp_showdown(d), FpStatus::AlreadySettled);
+
+            let (mut c, mut call) = (0, 0);
+            assert_eq!(fp_player(d, 3, &mut c, &mut call), FpStatus::Ok);
+            assert_eq!(c, 11);
+            assert_eq!(fp_player(d, 9, &mut c, &mut call), FpStatus::SeatNotFound);
+            fp_dealer_free(d);
+        }
+    }
+
+    #[test]
+    fn test_ffi_null() {
+        unsafe {
+            assert!(fp_dealer_new(1, ptr::null(), ptr::null(), 2).is_null());
+            assert_eq!(fp_new_hand(ptr::null_mut()), FpStatus::NullPointer);
+            fp_dealer_free(ptr::null_mut());
+        }
+    }
+}
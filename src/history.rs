@@ -0,0 +1,81 @@
+use crate::dealer::*;
+use crate::deck::*;
+
+// plain text export of the current or last hand, built from the ActionHistory
+// one line per entry, actions are "seat code value" using the ActionType codes, value is left out when 0
+//
+// seed: 123                     the hand's deck seed, see Dealer::hand_seed
+// button: 1
+// seat 2: 12 [As Kh Qd Jc]
+// preflop: 2 B 1, 3 R 1, 1 C
+// flop: [2c 5d 9h]
+// flop actions: 2 CK, 3 BA 8
+// rake: 2                       only when the showdown took rake
+// voided                        only when void_hand aborted the hand
+
+fn format_actions(dealer: &Dealer, indexes: &[usize]) -> String {
+    indexes.iter().map(|i| {
+        let a = &dealer.ah.actions[*i];
+        if a.value == 0 {
+            format!("{} {}", a.seat, a.t)
+        } else {
+            format!("{} {} {}", a.seat, a.t, a.value)
+        }
+    }).collect::<Vec<String>>().join(", ")
+}
+
+impl Dealer {
+    pub fn hand_history(&self) -> String {
+        let mut lines: Vec<String> = vec![
+            format!("seed: {}", self.hand_seed()),
+            format!("button: {}", self.button),
+        ];
+        for sb in &self.ah.start_bal {
+            let hand = self.p.iter().find(|p| p.seat == sb.seat)
+                .map(|p| p.hand.iter().map(card_to_string).collect::<Vec<String>>().join(" "))
+                .unwrap_or_default();
+            lines.push(format!("seat {}: {} [{}]", sb.seat, sb.bal, hand));
+        }
+        lines.push(format!("preflop: {}", format_actions(self, &self.ah.pf)));
+        if self.flop[0].value != 0 {
+            let flop = self.flop.iter().map(card_to_string).collect::<Vec<String>>().join(" ");
+            lines.push(format!("flop: [{}]", flop));
+            lines.push(format!("flop actions: {}", format_actions(self, &self.ah.f)));
+        }
+        if self.rake > 0 {
+            lines.push(format!("rake: {}", self.rake));
+        }
+        if self.ah.voided {
+            lines.push("voided".to_string());
+        }
+        lines.join("\n")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::player::*;
+
+    #[test]
+    fn test_hand_history() {
+        let mut dealer = Dealer::new(123, vec![
+            Player::new(1, 5),
+            Player::new(2, 5),
+            Player::new(3, 5),
+        ]);
+        dealer.new_hand();
+        dealer.p_action(Action {
+            seat: 1,
+            t: ActionType::Call,
+            value: 0
+        });
+        let hh = dealer.hand_history();
+        let lines: Vec<&str> = hh.lines().collect();
+        assert_eq!(lines[0], "seed: 123");
+        assert_eq!(lines[1], "button: 1");
+        assert!(lines[2].starts_with("seat 2: 5 ["));
+        assert_eq!(lines[5], "preflop: 2 B 1, 3 R 1, 1 C");
+        assert_eq!(lines.len(), 6);
+    }
+}
@@ -0,0 +1,380 @@
+use crate::dealer::*;
+use crate::events::*;
+
+// contribution ledger, every chip a seat puts in the pot this hand is added to its Commitment
+// side pots and uncalled bets are worked out from the totals
+// instead of rebuilding them from the s_bets and partial paids
+//
+// the pots are layered by the all in amounts of the players still in
+//   committed 2: 10, 3: 4 all in, 1: 10 folded  ->  main 12 for [2, 3], side 12 for [2]
+// dead money, a missed sb posted on return, is left out of the layers and goes into the main pot
+// antes are layered like bets but dont count towards calls, a player all in on the ante wins what it matched
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Commitment {
+    pub seat: u8,
+    pub amt: u16,      // this hand
+    pub street: u16,   // this street
+    pub dead: u16      // part of amt posted dead, it doesnt count towards calls or all ins
+}
+
+impl Commitment {
+    pub fn new(seat: u8) -> Self {
+        Commitment { seat, amt: 0, street: 0, dead: 0 }
+    }
+
+    // what the seat has put in to play the hand, without dead money
+    pub fn live(&self) -> u16 {
+        self.amt - self.dead
+    }
+}
+
+// pots and commitments as they are right now, see Dealer::pot_breakdown
+#[derive(Debug, Clone, PartialEq)]
+pub struct PotBreakdown {
+    pub pots: Vec<SidePot>,
+    pub committed: Vec<Commitment>
+}
+
+// splits the committed chips into pots, main pot first
+// live is the seats that can still win, all_in the ones among them that cant put in any more
+// a pot is closed off at each all in amount, folded chips go into the pots they reach
+// the last pot has everything above the biggest all in
+// players that are not all in can win every pot, mid hand they can still put in the rest
+pub fn side_pots(committed: &[Commitment], live: &[u8], all_in: &[u8]) -> Vec<SidePot> {
+    let dead = committed.iter().map(|c| c.dead).sum::<u16>();
+    let committed = committed.iter().map(|c| Commitment { amt: c.live(), dead: 0, ..c.clone() }).collect::<Vec<Commitment>>();
+    let mut levels = committed.iter()
+        .filter(|c| live.contains(&c.seat) && all_in.contains(&c.seat) && c.amt > 0)
+        .map(|c| c.amt)
+        .collect::<Vec<u16>>();
+    levels.sort();
+    levels.dedup();
+    let mut pots: Vec<SidePot> = vec![];
+    let mut prev = 0;
+    for level in levels {
+        let value = committed.iter().map(|c| c.amt.min(level) - c.amt.min(prev)).sum::<u16>();
+        let contributors = committed.iter()
+            .filter(|c| live.contains(&c.seat) && (c.amt >= level || !all_in.contains(&c.seat)))
+            .map(|c| c.seat)
+            .collect::<Vec<u8>>();
+        pots.push(SidePot { value, contributors });
+        prev = level;
+    }
+    let rest = committed.iter().map(|c| c.amt - c.amt.min(prev)).sum::<u16>();
+    let contributors = committed.iter()
+        .filter(|c| live.contains(&c.seat) && (c.amt > prev || !all_in.contains(&c.seat)))
+        .map(|c| c.seat)
+        .collect::<Vec<u8>>();
+    if rest > 0 {
+        match pots.last_mut() {
+            // only folded players put in more than the biggest all in
+            Some(last) if contributors.is_empty() => last.value += rest,
+            None if contributors.is_empty() => pots.push(SidePot {
+                value: rest,
+                contributors: committed.iter().filter(|c| live.contains(&c.seat)).map(|c| c.seat).collect(),
+            }),
+            _ => pots.push(SidePot { value: rest, contributors }),
+        }
+    }
+    if dead > 0 {
+        match pots.first_mut() {
+            Some(main) => main.value += dead,
+            None => pots.push(SidePot {
+                value: dead,
+                contributors: committed.iter().filter(|c| live.contains(&c.seat)).map(|c| c.seat).collect(),
+            }),
+        }
+    }
+    pots
+}
+
+impl Dealer {
+    pub fn committed(&self, seat: &u8) -> u16 {
+        self.committed.iter().find(|c| &c.seat == seat).map(|c| c.amt).unwrap_or(0)
+    }
+
+    pub(crate) fn commit(&mut self, seat: &u8, amt: &u16) {
+        match self.committed.iter_mut().find(|c| &c.seat == seat) {
+            Some(c) => {
+                c.amt += amt;
+                c.street += amt;
+            }
+            None => self.committed.push(Commitment { seat: *seat, amt: *amt, street: *amt, dead: 0 }),
+        }
+    }
+
+    // chips that go in the pot without counting as a bet, only the main pot can pay them out
+    pub(crate) fn commit_dead(&mut self, seat: &u8, amt: &u16) {
+        match self.committed.iter_mut().find(|c| &c.seat == seat) {
+            Some(c) => {
+                c.amt += amt;
+                c.dead += amt;
+            }
+            None => self.committed.push(Commitment { seat: *seat, amt: *amt, street: 0, dead: *amt }),
+        }
+    }
+
+    // chips that go in the pot and the layers without counting as a bet
+    pub(crate) fn commit_ante(&mut self, seat: &u8, amt: &u16) {
+        match self.committed.iter_mut().find(|c| &c.seat == seat) {
+            Some(c) => c.amt += amt,
+            None => self.committed.push(Commitment { seat: *seat, amt: *amt, street: 0, dead: 0 }),
+        }
+    }
+
+    // called when a street's betting is over, gives back whatever nobody can call
+    pub(crate) fn close_street(&mut self) {
+        self.refund_excess();
+        for c in &mut self.committed {
+            c.street = 0;
+        }
+        self.min_raise = self.config.big_blind;
+        self.acted = vec![];
+        self.short_raises = 0;
+        self.raises = 0;
+    }
+
+    // pots for the players that did not fold, in hand order inside each pot
+    pub fn group_side_pots(&self) -> Vec<SidePot> {
+        let live = self.p.iter().filter(|p| !p.is_folded).map(|p| p.seat).collect::<Vec<u8>>();
+        let all_in = self.p.iter().filter(|p| p.is_all_in).map(|p| p.seat).collect::<Vec<u8>>();
+        side_pots(&self.committed, &live, &all_in)
+    }
+
+    // the pots as they stand mid hand, plus what every seat has put in this street and this hand
+    // the last pot can include a bet that has not been called yet
+    pub fn pot_breakdown(&self) -> PotBreakdown {
+        PotBreakdown {
+            pots: self.group_side_pots(),
+            committed: self.committed.clone(),
+        }
+    }
+
+    // gives back the part of the biggest commitment nobody matched, returns the refunded (seat, amt)
+    // covers a bet everyone folded to, a raise over several smaller all ins, and an all in bigger than every caller
+    // only call it once a street's betting is over, a bet that can still be called is not excess
+    pub fn refund_excess(&mut self) -> Vec<(u8, u16)> {
+        let top = match self.committed.iter().max_by_key(|c| c.live()) {
+            Some(c) => c.clone(),
+            None => return vec![],
+        };
+        let second = self.committed.iter().filter(|c| c.seat != top.seat).map(|c| c.live()).max().unwrap_or(0);
+        if top.live() <= second {
+            return vec![];
+        }
+        let excess = top.live() - second;
+        self.pay_from_pot(&top.seat, &excess);
+        let c = self.committed.iter_mut().find(|c| c.seat == top.seat).unwrap();
+        c.amt -= excess;
+        c.street = c.street.saturating_sub(excess);
+        self.refunds.push((top.seat, excess));
+        self.emit(DealerEvent::BetReturned { seat: top.seat, amt: excess });
+        vec![(top.seat, excess)]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::player::*;
+    use proptest::prelude::*;
+
+    fn c(seat: u8, amt: u16) -> Commitment {
+        Commitment { seat, amt, street: 0, dead: 0 }
+    }
+
+    #[test]
+    fn test_side_pots() {
+        let pots = side_pots(&[c(2, 10), c(3, 4), c(1, 10)], &[2, 3], &[3]);
+        assert_eq!(pots, vec![
+            SidePot { value: 12, contributors: vec![2, 3] },
+            SidePot { value: 12, contributors: vec![2] },
+        ]);
+        // folded player put in more than the live ones
+        let pots = side_pots(&[c(1, 2), c(2, 6), c(3, 2)], &[1, 3], &[]);
+        assert_eq!(pots, vec![SidePot { value: 10, contributors: vec![1, 3] }]);
+        let pots = side_pots(&[c(1, 2), c(2, 6), c(3, 2)], &[1, 3], &[1, 3]);
+        assert_eq!(pots, vec![SidePot { value: 10, contributors: vec![1, 3] }]);
+        let pots = side_pots(&[c(1, 3), c(2, 5), c(3, 8), c(4, 8)], &[1, 2, 3, 4], &[1, 2]);
+        assert_eq!(pots, vec![
+            SidePot { value: 12, contributors: vec![1, 2, 3, 4] },
+            SidePot { value: 6, contributors: vec![2, 3, 4] },
+            SidePot { value: 6, contributors: vec![3, 4] },
+        ]);
+        // dead money only goes to the main pot and doesnt raise the all in level
+        let pots = side_pots(&[Commitment { dead: 3, ..c(1, 7) }, c(2, 8), c(3, 8)], &[1, 2, 3], &[1]);
+        assert_eq!(pots, vec![
+            SidePot { value: 15, contributors: vec![1, 2, 3] },
+            SidePot { value: 8, contributors: vec![2, 3] },
+        ]);
+    }
+
+    #[test]
+    fn test_pot_breakdown() {
+        let mut dealer = Dealer::new(123, vec![
+            Player::new(1, 10),
+            Player::new(2, 3),
+            Player::new(3, 10),
+        ]);
+        dealer.new_hand();
+        dealer.p_action(Action {
+            seat: 1,
+            t: ActionType::Raise,
+            value: 3
+        });
+        // seat 2 all in preflop for 3
+        dealer.p_action(Action {
+            seat: 2,
+            t: ActionType::CallAI,
+            value: 0
+        });
+        let b = dealer.pot_breakdown();
+        assert_eq!(b.pots, vec![
+            SidePot { value: 8, contributors: vec![2, 3, 1] },
+            SidePot { value: 2, contributors: vec![3, 1] },
+        ]);
+        assert_eq!(b.committed, vec![c(2, 3), c(3, 2), c(1, 5)].into_iter().map(|c| Commitment { street: c.amt, ..c }).collect::<Vec<_>>());
+
+        dealer.p_action(Action {
+            seat: 3,
+            t: ActionType::Call,
+            value: 0
+        });
+        assert_eq!(dealer.stage, Stages::Flop);
+        let b = dealer.pot_breakdown();
+        assert_eq!(b.pots, vec![
+            SidePot { value: 9, contributors: vec![2, 3, 1] },
+            SidePot { value: 4, contributors: vec![3, 1] },
+        ]);
+        assert!(b.committed.iter().all(|c| c.street == 0));
+        assert_eq!(b.pots.iter().map(|p| p.value).sum::<u16>(), dealer.pot);
+
+        dealer.p_action(Action {
+            seat: 3,
+            t: ActionType::Bet,
+            value: 2
+        });
+        assert_eq!(dealer.pot_breakdown().committed[1], Commitment { seat: 3, amt: 7, street: 2, dead: 0 });
+    }
+
+    #[test]
+    fn test_refund_at_street_close() {
+        // two short all ins under a raise
+        let mut dealer = Dealer::new(123, vec![
+            Player::new(1, 20),
+            Player::new(2, 4),
+            Player::new(3, 6),
+        ]);
+        dealer.new_hand();
+        for (seat, t, value) in [
+            (1, ActionType::Raise, 5),
+            (2, ActionType::CallAI, 0),
+            (3, ActionType::CallAI, 0),
+        ] {
+            dealer.try_p_action(Action { seat, t, value }).unwrap();
+        }
+        assert_eq!(dealer.stage, Stages::AwaitingSettlement);
+        // seat 1 put in 7, only 6 could be called
+        assert_eq!(dealer.p.iter().find(|p| p.seat == 1).unwrap().chips, 14);
+        assert_eq!(dealer.refunds, vec![(1, 1)]);
+        assert_eq!(dealer.pot, 16);
+        assert_eq!(dealer.pot_breakdown().pots, vec![
+            SidePot { value: 12, contributors: vec![2, 3, 1] },
+            SidePot { value: 4, contributors: vec![3, 1] },
+        ]);
+        assert_eq!(dealer.handle_showdown().refunds, vec![(1, 1)]);
+
+        // a flop bet only partly called by an all in, the last player folds
+        let mut dealer = Dealer::new(123, vec![
+            Player::new(1, 10),
+            Player::new(2, 10),
+            Player::new(3, 4),
+        ]);
+        dealer.new_hand();
+        for (seat, t, value) in [
+            (1, ActionType::Call, 0),
+            (2, ActionType::Call, 0),
+            (3, ActionType::Check, 0),
+            (2, ActionType::Bet, 6),
+            (3, ActionType::CallAI, 0),
+        ] {
+            dealer.try_p_action(Action { seat, t, value }).unwrap();
+        }
+        assert!(dealer.refunds.is_empty());
+        dealer.try_p_action(Action { seat: 1, t: ActionType::Fold, value: 0 }).unwrap();
+        assert_eq!(dealer.stage, Stages::AwaitingSettlement);
+        assert_eq!(dealer.refunds, vec![(2, 4)]);
+        assert_eq!(dealer.p.iter().find(|p| p.seat == 2).unwrap().chips, 6);
+        assert_eq!(dealer.pot, 10);
+    }
+
+    proptest! {
+        #[test]
+        fn prop_side_pots_conserve_chips(amts in proptest::collection::vec(0_u16..200, 2..9), folded in proptest::collection::vec(any::<bool>(), 9), all_in in proptest::collection::vec(any::<bool>(), 9)) {
+            let committed = amts.iter().enumerate().map(|(i, amt)| c(i as u8 + 1, *amt)).collect::<Vec<Commitment>>();
+            let mut live = committed.iter().filter(|c| !folded[c.seat as usize - 1]).map(|c| c.seat).collect::<Vec<u8>>();
+            if live.is_empty() {
+                live.push(1);
+            }
+            let all_in = committed.iter().filter(|c| all_in[c.seat as usize - 1]).map(|c| c.seat).collect::<Vec<u8>>();
+            let pots = side_pots(&committed, &live, &all_in);
+            let total = committed.iter().map(|c| c.amt).sum::<u16>();
+            if committed.iter().any(|c| live.contains(&c.seat) && c.amt > 0) {
+                prop_assert_eq!(pots.iter().map(|p| p.value).sum::<u16>(), total);
+            }
+            for pot in &pots {
+                prop_assert!(!pot.contributors.is_empty());
+                prop_assert!(pot.contributors.iter().all(|s| live.contains(s)));
+            }
+        }
+
+        // plays random legal actions to the end of the hand
+        // chips in front of the players plus the pot never change, and the pot is empty once settled
+        #[test]
+        fn prop_hand_conserves_chips(seed in any::<u64>(), stacks in proptest::collection::vec(1_u16..60, 2..7), choices in proptest::collection::vec(any::<u16>(), 64)) {
+            let p = stacks.iter().enumerate().map(|(i, chips)| Player::new(i as u8 + 1, *chips)).collect::<Vec<Player>>();
+            let total = stacks.iter().sum::<u16>();
+            let mut dealer = Dealer::new(seed, p);
+            dealer.new_hand();
+            let mut i = 0;
+            while dealer.stage.is_betting() {
+                let seat = dealer.curr;
+                let actions = dealer.get_available_actions();
+                let choice = choices[i % choices.len()];
+                i += 1;
+                let t = actions[choice as usize % actions.len()].clone();
+                let chips = dealer.p.iter().find(|p| p.seat == seat).unwrap().chips;
+                let call_amt = dealer.get_call_amt(&seat);
+                let (min, max) = dealer.get_bet_range();
+                let value = match t {
+                    ActionType::Bet => min + choice % (max - min + 1),
+                    ActionType::BetAI => chips,
+                    ActionType::Raise => 1 + choice % max.max(1),
+                    ActionType::RaiseAI => chips - call_amt,
+                    _ => 0,
+                };
+                let action = Action { seat, t, value };
+                let action = if dealer.check_action(&action).is_ok() {
+                    action
+                } else if actions.contains(&ActionType::Check) {
+                    Action { seat, t: ActionType::Check, value: 0 }
+                } else {
+                    Action { seat, t: ActionType::Fold, value: 0 }
+                };
+                dealer.p_action(action);
+                prop_assert_eq!(dealer.p.iter().map(|p| p.chips).sum::<u16>() + dealer.pot, total);
+                prop_assert_eq!(dealer.committed.iter().map(|c| c.amt).sum::<u16>(), dealer.pot);
+            }
+            let result = dealer.handle_showdown();
+            prop_assert_eq!(dealer.pot, 0);
+            prop_assert_eq!(dealer.p.iter().map(|p| p.chips).sum::<u16>(), total);
+            // every seat ends with what it started with, less what it left in the pot, plus what it won
+            for sb in &dealer.ah.start_bal {
+                let chips = dealer.p.iter().find(|p| p.seat == sb.seat).unwrap().chips;
+                let won = result.awarded.iter().find(|(seat, _)| *seat == sb.seat).map(|(_, amt)| *amt).unwrap_or(0);
+                prop_assert_eq!(chips + dealer.committed(&sb.seat), sb.bal + won);
+            }
+        }
+    }
+}
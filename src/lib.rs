@@ -1,6 +1,49 @@
+mod error;
+pub use error::*;
 mod deck;
 pub use deck::*;
 mod player;
 pub use player::*;
 mod dealer;
 pub use dealer::*;
+mod config;
+pub use config::*;
+mod blinds;
+mod seating;
+pub use seating::*;
+mod rake;
+mod clock;
+pub use clock::*;
+mod advance;
+pub use advance::*;
+mod tournament;
+pub use tournament::*;
+mod events;
+pub use events::*;
+mod ledger;
+pub use ledger::*;
+mod obs;
+pub use obs::*;
+mod history;
+mod view;
+pub use view::*;
+mod acpc;
+pub use acpc::*;
+#[cfg(feature = "python")]
+mod python;
+#[cfg(feature = "ffi")]
+mod ffi;
+#[cfg(feature = "ffi")]
+pub use ffi::*;
+#[cfg(feature = "wasm")]
+mod wasm;
+#[cfg(feature = "wasm")]
+pub use wasm::*;
+#[cfg(feature = "bots")]
+mod bot;
+#[cfg(feature = "bots")]
+pub use bot::*;
+#[cfg(feature = "server")]
+mod server;
+#[cfg(feature = "server")]
+pub use server::*;
@@ -0,0 +1,215 @@
+use rs_handstrength::{Card, Suit};
+use crate::dealer::*;
+
+// fixed size observation encoding of a seat's view of the dealer, for learning agents
+// every value is an f32, chip amounts are divided by the big blind
+// seats are relative to the observing seat, going clockwise by seat number, 0 is always the observer
+//
+// layout for OBS_VERSION 2, offsets are the consts below
+//   HOLE_OFFSET      52  one hot of the observer's 4 hole cards, index suit * 13 + value - 1, suits S H C D
+//   FLOP_OFFSET      52  one hot of the flop cards, all 0 until the flop is dealt
+//   STAGE_OFFSET      3  one hot of PreFlop, Flop, no betting (WaitingForHand, AwaitingSettlement, Settled)
+//   POT_OFFSET        1  pot / bb
+//   CALL_OFFSET       1  observer's call amount / bb
+//   POSITION_OFFSET   9  one hot of the observer's position, 0 is left of the button, button is the last dealt in seat
+//   SEATS_OFFSET     54  9 blocks of [present, stack / bb, folded, all in, is button, is curr] by relative seat
+//   ACTIONS_OFFSET  608  ah.pf then ah.f, 16 slots each, slot is [valid, 9 relative seat one hot, 8 action type one hot, value / bb]
+//   LEGAL_OFFSET      8  legal action mask in ActionType order, all 0 unless the observer is curr
+// actions past the 16th of a street are dropped
+// bump OBS_VERSION whenever the layout changes so trained models dont get fed the wrong features
+// 2: STAGE has the new stages, POSITION follows the dead button and the heads up button posting the sb,
+//    and chip amounts are divided by the tables configured bb instead of BIG_BLIND
+
+pub const OBS_VERSION: u32 = 2;
+pub const OBS_MAX_SEATS: usize = 9;
+pub const OBS_STREET_ACTIONS: usize = 16;
+
+const NUM_CARDS: usize = 52;
+const NUM_STAGES: usize = 3;
+const NUM_ACTION_TYPES: usize = 8;
+const SEAT_FEATURES: usize = 6;
+const ACTION_FEATURES: usize = 1 + OBS_MAX_SEATS + NUM_ACTION_TYPES + 1;
+
+pub const HOLE_OFFSET: usize = 0;
+pub const FLOP_OFFSET: usize = HOLE_OFFSET + NUM_CARDS;
+pub const STAGE_OFFSET: usize = FLOP_OFFSET + NUM_CARDS;
+pub const POT_OFFSET: usize = STAGE_OFFSET + NUM_STAGES;
+pub const CALL_OFFSET: usize = POT_OFFSET + 1;
+pub const POSITION_OFFSET: usize = CALL_OFFSET + 1;
+pub const SEATS_OFFSET: usize = POSITION_OFFSET + OBS_MAX_SEATS;
+pub const ACTIONS_OFFSET: usize = SEATS_OFFSET + OBS_MAX_SEATS * SEAT_FEATURES;
+pub const LEGAL_OFFSET: usize = ACTIONS_OFFSET + 2 * OBS_STREET_ACTIONS * ACTION_FEATURES;
+pub const OBS_LEN: usize = LEGAL_OFFSET + NUM_ACTION_TYPES;
+
+// index of a card in a 52 card one hot, None for the default card used before dealing
+pub fn card_index(card: &Card) -> Option<usize> {
+    if card.value < 1 || card.value > 13 {
+        return None;
+    }
+    let suit = [Suit::S, Suit::H, Suit::C, Suit::D].iter().position(|s| *s == card.suit)?;
+    Some(suit * 13 + card.value as usize - 1)
+}
+
+pub fn action_type_index(t: &ActionType) -> usize {
+    match t {
+        ActionType::Fold => 0,
+        ActionType::Check => 1,
+        ActionType::Call => 2,
+        ActionType::CallAI => 3,
+        ActionType::Bet => 4,
+        ActionType::BetAI => 5,
+        ActionType::Raise => 6,
+        ActionType::RaiseAI => 7,
+    }
+}
+
+// encode seat's view of the dealer into a OBS_LEN vec
+// should panic if the seat is not at the table or there are more than OBS_MAX_SEATS players
+pub fn encode_obs(dealer: &Dealer, seat: u8) -> Vec<f32> {
+    let mut obs = vec![0_f32; OBS_LEN];
+    let bb = dealer.config.big_blind as f32;
+
+    let mut seats: Vec<u8> = dealer.p.iter().map(|p| p.seat).collect();
+    seats.sort();
+    let num_p = seats.len();
+    if num_p > OBS_MAX_SEATS {
+        panic!("encode_obs too many players {:?}", num_p);
+    }
+    let self_i = seats.iter().position(|s| *s == seat).expect("encode_obs seat not found");
+    let rel = |s: u8| -> Option<usize> {
+        seats.iter().position(|x| *x == s).map(|i| (i + num_p - self_i) % num_p)
+    };
+
+    let player = dealer.p.iter().find(|p| p.seat == seat).unwrap();
+    for card in &player.hand {
+        if let Some(i) = card_index(card) {
+            obs[HOLE_OFFSET + i] = 1.0;
+        }
+    }
+    for card in &dealer.flop {
+        if let Some(i) = card_index(card) {
+            obs[FLOP_OFFSET + i] = 1.0;
+        }
+    }
+
+    let stage_i = match dealer.stage {
+        Stages::PreFlop => 0,
+        Stages::Flop => 1,
+        Stages::WaitingForHand | Stages::AwaitingSettlement | Stages::Settled => 2,
+    };
+    obs[STAGE_OFFSET + stage_i] = 1.0;
+    obs[POT_OFFSET] = dealer.pot as f32 / bb;
+    obs[CALL_OFFSET] = dealer.get_call_amt(&seat) as f32 / bb;
+
+    // the button is the last position, 0 is left of the button and first to act preflop unless heads up
+    if let Some(button_i) = seats.iter().position(|s| *s == dealer.button) {
+        let position = (self_i + num_p - button_i - 1) % num_p;
+        obs[POSITION_OFFSET + position] = 1.0;
+    }
+
+    for p in &dealer.p {
+        let i = SEATS_OFFSET + rel(p.seat).unwrap() * SEAT_FEATURES;
+        obs[i] = 1.0;
+        obs[i + 1] = p.chips as f32 / bb;
+        obs[i + 2] = p.is_folded as u8 as f32;
+        obs[i + 3] = p.is_all_in as u8 as f32;
+        obs[i + 4] = (p.seat == dealer.button) as u8 as f32;
+        obs[i + 5] = (p.seat == dealer.curr && dealer.stage.is_betting()) as u8 as f32;
+    }
+
+    for (street, indexes) in [&dealer.ah.pf, &dealer.ah.f].iter().enumerate() {
+        for (slot, a_i) in indexes.iter().take(OBS_STREET_ACTIONS).enumerate() {
+            let action = &dealer.ah.actions[*a_i];
+            let i = ACTIONS_OFFSET + (street * OBS_STREET_ACTIONS + slot) * ACTION_FEATURES;
+            obs[i] = 1.0;
+            if let Some(r) = rel(action.seat) {
+                obs[i + 1 + r] = 1.0;
+            }
+            obs[i + 1 + OBS_MAX_SEATS + action_type_index(&action.t)] = 1.0;
+            obs[i + ACTION_FEATURES - 1] = action.value as f32 / bb;
+        }
+    }
+
+    if dealer.stage.is_betting() && dealer.curr == seat {
+        for t in dealer.get_available_actions() {
+            obs[LEGAL_OFFSET + action_type_index(&t)] = 1.0;
+        }
+    }
+
+    obs
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::player::*;
+
+    fn new_dealer() -> Dealer {
+        let mut dealer = Dealer::new(123, vec![
+            Player::new(1, 15),
+            Player::new(2, 12),
+            Player::new(3, 10),
+        ]);
+        dealer.new_hand();
+        dealer
+    }
+
+    #[test]
+    fn test_obs_len_and_cards() {
+        let dealer = new_dealer();
+        let obs = encode_obs(&dealer, 1);
+        assert_eq!(obs.len(), OBS_LEN);
+        let hole_count = obs[HOLE_OFFSET..FLOP_OFFSET].iter().filter(|x| **x == 1.0).count();
+        assert_eq!(hole_count, 4);
+        let flop_count = obs[FLOP_OFFSET..STAGE_OFFSET].iter().filter(|x| **x == 1.0).count();
+        assert_eq!(flop_count, 0);
+        assert_eq!(obs[STAGE_OFFSET], 1.0);
+        assert_eq!(obs[POT_OFFSET], 1.5);
+        assert_eq!(obs[CALL_OFFSET], 1.0);
+    }
+
+    #[test]
+    fn test_obs_position_and_seats() {
+        let dealer = new_dealer();
+        // seat 1 is the button
+        let obs = encode_obs(&dealer, 1);
+        assert_eq!(obs[POSITION_OFFSET + 2], 1.0);
+        // observer block is first, seat 2 is next, seat 3 after
+        assert_eq!(obs[SEATS_OFFSET + 4], 1.0);
+        assert_eq!(obs[SEATS_OFFSET + 1], 7.5);
+        assert_eq!(obs[SEATS_OFFSET + SEAT_FEATURES + 1], 5.5);
+        assert_eq!(obs[SEATS_OFFSET + 2 * SEAT_FEATURES + 1], 4.0);
+        // no 4th seat
+        assert_eq!(obs[SEATS_OFFSET + 3 * SEAT_FEATURES], 0.0);
+
+        let obs2 = encode_obs(&dealer, 2);
+        assert_eq!(obs2[POSITION_OFFSET], 1.0);
+        assert_eq!(obs2[SEATS_OFFSET + 2 * SEAT_FEATURES + 4], 1.0);
+    }
+
+    #[test]
+    fn test_obs_actions_and_legal_mask() {
+        let mut dealer = new_dealer();
+        dealer.p_action(Action {
+            seat: 1,
+            t: ActionType::Call,
+            value: 0
+        });
+        let obs = encode_obs(&dealer, 2);
+        // sb bet, bb raise, call
+        for slot in 0..3 {
+            assert_eq!(obs[ACTIONS_OFFSET + slot * ACTION_FEATURES], 1.0);
+        }
+        assert_eq!(obs[ACTIONS_OFFSET + 3 * ACTION_FEATURES], 0.0);
+        // first action is seat 2 sb bet, relative seat 0
+        assert_eq!(obs[ACTIONS_OFFSET + 1], 1.0);
+        assert_eq!(obs[ACTIONS_OFFSET + 1 + OBS_MAX_SEATS + action_type_index(&ActionType::Bet)], 1.0);
+        assert_eq!(obs[ACTIONS_OFFSET + ACTION_FEATURES - 1], 0.5);
+
+        // seat 2 is curr, seat 3 is not
+        let mask = &obs[LEGAL_OFFSET..];
+        assert_eq!(mask, &[1.0, 0.0, 1.0, 0.0, 0.0, 0.0, 1.0, 0.0]);
+        let obs3 = encode_obs(&dealer, 3);
+        assert!(obs3[LEGAL_OFFSET..].iter().all(|x| *x == 0.0));
+    }
+}
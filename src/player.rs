@@ -1,5 +1,6 @@
 use rs_handstrength::Card;
 use std::default::Default;
+use crate::config::*;
 
 #[derive(Debug, Clone)]
 pub struct Player {
@@ -8,6 +9,10 @@ pub struct Player {
     pub is_all_in: bool,
     pub is_folded: bool,
     pub hand: [Card; 4],
+    pub sitting_out: bool,   // not dealt in, new_hand folds them before the cards go out
+    pub missed_sb: bool,     // blinds that went past while sitting out, owed before playing again
+    pub missed_bb: bool,
+    pub time_bank: u64,      // ms left to use once the action time runs out
 }
 
 impl Player {
@@ -20,9 +25,23 @@ impl Player {
             is_all_in: false,
             is_folded: false,
             hand: [Card::default(); 4],
+            sitting_out: false,
+            missed_sb: false,
+            missed_bb: false,
+            time_bank: 0,
         }
     }
 
+    // missed blinds to post before the player is dealt in again, at the blinds in config
+    pub fn owed_blinds(&self, config: &TableConfig) -> u16 {
+        self.missed_sb as u16 * config.small_blind + self.missed_bb as u16 * config.big_blind
+    }
+
+    // dealt into the next hand, needs to be sitting in with more chips than it owes
+    pub fn can_play(&self, config: &TableConfig) -> bool {
+        !self.sitting_out && self.chips > self.owed_blinds(config)
+    }
+
 
     pub fn add_chips(&mut self, &amt: &u16) {
         self.chips += amt;
@@ -44,6 +63,7 @@ impl Player {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::dealer::{SMALL_BLIND, BIG_BLIND};
     use rs_handstrength::Suit;
     #[test]
     fn test_new() {
@@ -53,6 +73,23 @@ mod tests {
         assert_eq!(player.is_all_in, false);
         assert_eq!(player.is_folded, false);
         assert_eq!(player.hand, [Card::default(); 4]);
+        assert!(player.can_play(&TableConfig::default()));
+    }
+
+    #[test]
+    fn test_owed_blinds() {
+        let config = TableConfig::default();
+        let mut player = Player::new(1, 3);
+        player.missed_sb = true;
+        player.missed_bb = true;
+        assert_eq!(player.owed_blinds(&config), SMALL_BLIND + BIG_BLIND);
+        assert!(!player.can_play(&config));
+        player.add_chips(&1);
+        assert!(player.can_play(&config));
+        // owed at the blinds the table has now
+        assert_eq!(player.owed_blinds(&TableConfig { small_blind: 5, big_blind: 10, ..config.clone() }), 15);
+        player.sitting_out = true;
+        assert!(!player.can_play(&config));
     }
 
  
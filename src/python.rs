@@ -0,0 +1,365 @@
+use pyo3::prelude::*;
+use pyo3::exceptions::PyValueError;
+
+use crate::dealer::*;
+use crate::deck::*;
+use crate::error::*;
+use crate::obs::*;
+use crate::player::*;
+
+// python bindings, built with the python feature
+// maturin build passes --crate-type cdylib itself, the manifest only builds the rlib
+// ActionType and Stages are passed around as their short string codes, cards as "As" style strings
+
+fn parse_action_type(t: &str) -> PyResult<ActionType> {
+    t.parse::<ActionType>().map_err(PyValueError::new_err)
+}
+
+fn stage_str(stage: &Stages) -> &'static str {
+    match stage {
+        Stages::WaitingForHand => "WaitingForHand",
+        Stages::PreFlop => "PreFlop",
+        Stages::Flop => "Flop",
+        Stages::AwaitingSettlement => "AwaitingSettlement",
+        Stages::Settled => "Settled",
+    }
+}
+
+fn cards_to_strings(cards: &[rs_handstrength::Card]) -> Vec<String> {
+    cards.iter().map(card_to_string).collect()
+}
+
+#[pyclass(name = "Player", module = "rs_floppoker", skip_from_py_object)]
+#[derive(Clone)]
+pub struct PyPlayer {
+    pub inner: Player,
+}
+
+#[pymethods]
+impl PyPlayer {
+    #[new]
+    fn new(seat: u8, chips: u16) -> Self {
+        PyPlayer { inner: Player::new(seat, chips) }
+    }
+    #[getter]
+    fn seat(&self) -> u8 {
+        self.inner.seat
+    }
+    #[getter]
+    fn chips(&self) -> u16 {
+        self.inner.chips
+    }
+    #[getter]
+    fn is_all_in(&self) -> bool {
+        self.inner.is_all_in
+    }
+    #[getter]
+    fn is_folded(&self) -> bool {
+        self.inner.is_folded
+    }
+    #[getter]
+    fn hand(&self) -> Vec<String> {
+        cards_to_strings(&self.inner.hand)
+    }
+    fn __repr__(&self) -> String {
+        format!("Player(seat={}, chips={})", self.inner.seat, self.inner.chips)
+    }
+}
+
+#[pyclass(name = "Action", module = "rs_floppoker", skip_from_py_object)]
+#[derive(Clone)]
+pub struct PyAction {
+    pub inner: Action,
+}
+
+#[pymethods]
+impl PyAction {
+    #[new]
+    #[pyo3(signature = (seat, t, value = 0))]
+    fn new(seat: u8, t: &str, value: u16) -> PyResult<Self> {
+        Ok(PyAction { inner: Action { seat, t: parse_action_type(t)?, value } })
+    }
+    #[getter]
+    fn seat(&self) -> u8 {
+        self.inner.seat
+    }
+    #[getter]
+    fn t(&self) -> String {
+        self.inner.t.to_string()
+    }
+    #[getter]
+    fn value(&self) -> u16 {
+        self.inner.value
+    }
+    fn __repr__(&self) -> String {
+        format!("Action(seat={}, t={:?}, value={})", self.inner.seat, self.inner.t.to_string(), self.inner.value)
+    }
+}
+
+// dealer errors come out as ValueError so python never sees a panic
+fn to_py(e: DealerError) -> PyErr {
+    PyValueError::new_err(e.to_string())
+}
+
+// encode_obs checks, so a bad seat or too many players is a ValueError
+fn obs_for(dealer: &Dealer, seat: u8) -> PyResult<Vec<f32>> {
+    if dealer.p.len() > OBS_MAX_SEATS {
+        return Err(PyValueError::new_err(format!("at most {} players", OBS_MAX_SEATS)));
+    }
+    if !dealer.p.iter().any(|p| p.seat == seat) {
+        return Err(to_py(DealerError::SeatNotFound(seat)));
+    }
+    Ok(encode_obs(dealer, seat))
+}
+
+#[pyclass(name = "PotResult", module = "rs_floppoker", skip_from_py_object)]
+#[derive(Clone)]
+pub struct PyPotResult {
+    pub inner: PotResult,
+}
+
+#[pymethods]
+impl PyPotResult {
+    #[getter]
+    fn value(&self) -> u16 {
+        self.inner.value
+    }
+    #[getter]
+    fn eligible(&self) -> Vec<u8> {
+        self.inner.eligible.clone()
+    }
+    #[getter]
+    fn equities(&self) -> Vec<(u8, f32)> {
+        self.inner.equities.clone()
+    }
+    #[getter]
+    fn awards(&self) -> Vec<(u8, u16)> {
+        self.inner.awards.clone()
+    }
+    fn __repr__(&self) -> String {
+        format!("PotResult(value={}, awards={:?})", self.inner.value, self.inner.awards)
+    }
+}
+
+#[pyclass(name = "ShowdownResult", module = "rs_floppoker", skip_from_py_object)]
+#[derive(Clone)]
+pub struct PyShowdownResult {
+    pub inner: ShowdownResult,
+}
+
+#[pymethods]
+impl PyShowdownResult {
+    // main pot first
+    #[getter]
+    fn pots(&self) -> Vec<PyPotResult> {
+        self.inner.pots.iter().map(|p| PyPotResult { inner: p.clone() }).collect()
+    }
+    #[getter]
+    fn awarded(&self) -> Vec<(u8, u16)> {
+        self.inner.awarded.clone()
+    }
+    #[getter]
+    fn refunds(&self) -> Vec<(u8, u16)> {
+        self.inner.refunds.clone()
+    }
+    #[getter]
+    fn odd_chips(&self) -> Vec<(u8, u16)> {
+        self.inner.odd_chips.clone()
+    }
+    #[getter]
+    fn rake(&self) -> u16 {
+        self.inner.rake
+    }
+    fn __repr__(&self) -> String {
+        format!("ShowdownResult(awarded={:?}, rake={})", self.inner.awarded, self.inner.rake)
+    }
+}
+
+#[pyclass(name = "Dealer", module = "rs_floppoker")]
+pub struct PyDealer {
+    pub inner: Dealer,
+}
+
+#[pymethods]
+impl PyDealer {
+    #[new]
+    fn new(seed: u64, players: Vec<PyRef<'_, PyPlayer>>) -> Self {
+        let p = players.iter().map(|p| p.inner.clone()).collect::<Vec<Player>>();
+        PyDealer { inner: Dealer::new(seed, p) }
+    }
+    fn new_hand(&mut self) -> PyResult<()> {
+        self.inner.try_new_hand().map_err(to_py)
+    }
+    fn p_action(&mut self, action: PyRef<'_, PyAction>) -> PyResult<()> {
+        self.inner.try_p_action(action.inner.clone()).map_err(to_py)
+    }
+    fn get_available_actions(&self) -> PyResult<Vec<String>> {
+        if !self.inner.stage.is_betting() {
+            return Err(PyValueError::new_err("no hand in progress"));
+        }
+        Ok(self.inner.get_available_actions().iter().map(|t| t.to_string()).collect())
+    }
+    fn get_call_amt(&self, seat: u8) -> u16 {
+        self.inner.get_call_amt(&seat)
+    }
+    fn handle_showdown(&mut self) -> PyResult<PyShowdownResult> {
+        let inner = self.inner.try_handle_showdown().map_err(to_py)?;
+        Ok(PyShowdownResult { inner })
+    }
+    fn hand_history(&self) -> String {
+        self.inner.hand_history()
+    }
+    fn encode_obs(&self, seat: u8) -> PyResult<Vec<f32>> {
+        obs_for(&self.inner, seat)
+    }
+    #[getter]
+    fn players(&self) -> Vec<PyPlayer> {
+        self.inner.p.iter().map(|p| PyPlayer { inner: p.clone() }).collect()
+    }
+    #[getter]
+    fn stage(&self) -> &'static str {
+        stage_str(&self.inner.stage)
+    }
+    #[getter]
+    fn curr(&self) -> u8 {
+        self.inner.curr
+    }
+    #[getter]
+    fn button(&self) -> u8 {
+        self.inner.button
+    }
+    #[getter]
+    fn pot(&self) -> u16 {
+        self.inner.pot
+    }
+    #[getter]
+    fn flop(&self) -> Vec<String> {
+        cards_to_strings(&self.inner.flop)
+    }
+}
+
+// reinforcement learning style wrapper, every reset deals a fresh hand from the starting stacks
+// step takes the current seat's action and returns (curr, obs, rewards, done)
+// obs is encode_obs for the next seat to act, rewards are chip deltas by seat order of the stacks list
+// and are all 0 until the hand is settled
+#[pyclass(name = "FlopPokerEnv", module = "rs_floppoker")]
+pub struct PyEnv {
+    pub dealer: Dealer,
+    pub stacks: Vec<u16>,
+    pub seed: u64,
+}
+
+impl PyEnv {
+    fn rewards(&self) -> Vec<i32> {
+        self.stacks.iter().enumerate().map(|(i, chips)| {
+            let seat = i as u8 + 1;
+            let p = self.dealer.p.iter().find(|p| p.seat == seat).unwrap();
+            p.chips as i32 - *chips as i32
+        }).collect()
+    }
+}
+
+#[pymethods]
+impl PyEnv {
+    #[new]
+    #[pyo3(signature = (stacks, seed = 0))]
+    fn new(stacks: Vec<u16>, seed: u64) -> PyResult<Self> {
+        if stacks.len() < 2 {
+            return Err(PyValueError::new_err("need at least 2 players"));
+        }
+        if stacks.len() > OBS_MAX_SEATS {
+            return Err(PyValueError::new_err(format!("at most {} players", OBS_MAX_SEATS)));
+        }
+        if stacks.contains(&0) {
+            return Err(PyValueError::new_err("every stack needs chips"));
+        }
+        let p = stacks.iter().enumerate().map(|(i, chips)| Player::new(i as u8 + 1, *chips)).collect();
+        Ok(PyEnv { dealer: Dealer::new(seed, p), stacks, seed })
+    }
+    // deals a new hand, uses the next seed if none is given
+    #[pyo3(signature = (seed = None))]
+    fn reset(&mut self, seed: Option<u64>) -> PyResult<(u8, Vec<f32>)> {
+        self.seed = seed.unwrap_or(self.seed.wrapping_add(1));
+        let p = self.stacks.iter().enumerate().map(|(i, chips)| Player::new(i as u8 + 1, *chips)).collect();
+        self.dealer = Dealer::new(self.seed, p);
+        self.dealer.try_new_hand().map_err(to_py)?;
+        let curr = self.dealer.curr;
+        Ok((curr, obs_for(&self.dealer, curr)?))
+    }
+    fn legal_actions(&self) -> Vec<String> {
+        if !self.dealer.stage.is_betting() {
+            return vec![];
+        }
+        self.dealer.get_available_actions().iter().map(|t| t.to_string()).collect()
+    }
+    #[pyo3(signature = (t, value = 0))]
+    fn step(&mut self, t: &str, value: u16) -> PyResult<(u8, Vec<f32>, Vec<i32>, bool)> {
+        let action = Action { seat: self.dealer.curr, t: parse_action_type(t)?, value };
+        self.dealer.try_p_action(action).map_err(to_py)?;
+        let curr = self.dealer.curr;
+        if self.dealer.stage == Stages::AwaitingSettlement {
+            self.dealer.try_handle_showdown().map_err(to_py)?;
+            return Ok((curr, obs_for(&self.dealer, curr)?, self.rewards(), true));
+        }
+        Ok((curr, obs_for(&self.dealer, curr)?, vec![0; self.stacks.len()], false))
+    }
+    #[getter]
+    fn dealer(&self) -> PyDealer {
+        PyDealer { inner: self.dealer.clone() }
+    }
+    #[getter]
+    fn obs_len(&self) -> usize {
+        OBS_LEN
+    }
+}
+
+#[pymodule]
+fn rs_floppoker(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_class::<PyPlayer>()?;
+    m.add_class::<PyAction>()?;
+    m.add_class::<PyDealer>()?;
+    m.add_class::<PyEnv>()?;
+    m.add_class::<PyShowdownResult>()?;
+    m.add_class::<PyPotResult>()?;
+    m.add("OBS_VERSION", OBS_VERSION)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_env_new_checks() {
+        assert!(PyEnv::new(vec![50], 0).is_err());
+        assert!(PyEnv::new(vec![50; OBS_MAX_SEATS + 1], 0).is_err());
+        assert!(PyEnv::new(vec![50, 0], 0).is_err());
+        assert!(PyEnv::new(vec![50; OBS_MAX_SEATS], 0).is_ok());
+    }
+
+    #[test]
+    fn test_env_reset_step_rewards() {
+        let mut env = PyEnv::new(vec![50, 50, 50], u64::MAX).unwrap();
+        let (curr, obs) = env.reset(None).unwrap();
+        assert_eq!(env.seed, 0);
+        assert_eq!(obs.len(), OBS_LEN);
+        assert_eq!(curr, env.dealer.curr);
+        assert!(env.step("Z", 0).is_err());
+        // everyone folds to the bb
+        let mut done = false;
+        let mut rewards = vec![];
+        while !done {
+            let (_, obs, r, d) = env.step("F", 0).unwrap();
+            assert_eq!(obs.len(), OBS_LEN);
+            (rewards, done) = (r, d);
+        }
+        let bb = env.dealer.bb;
+        let sb = env.dealer.sb;
+        assert_eq!(rewards.iter().sum::<i32>(), 0);
+        assert_eq!(rewards[bb as usize - 1], SMALL_BLIND as i32);
+        assert_eq!(rewards[sb as usize - 1], -(SMALL_BLIND as i32));
+        assert_eq!(env.dealer.stage, Stages::Settled);
+        // the hand is over, acting again is an error and not a panic
+        assert!(env.step("F", 0).is_err());
+    }
+}
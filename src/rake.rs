@@ -0,0 +1,190 @@
+use crate::dealer::*;
+use crate::events::*;
+
+// rake for real money tables, set in TableConfig
+// handle_showdown takes it out of the pot before any of it is split by equity
+// it comes out of the main pot first, then the side pots in order, so the chips still add up
+//   pot 40, 5% with a cap of 1 for 2 players and 3 for 3 or more, 3 players dealt in  ->  rake 2
+// the rake is floored, a pot too small to owe a whole chip pays nothing
+
+impl Dealer {
+    // the most rake a pot can pay with the players dealt in this hand, None when there is no cap
+    pub fn rake_cap(&self) -> Option<u16> {
+        let dealt = self.ah.start_bal.len() as u8;
+        self.config.rake_caps.iter()
+            .filter(|(players, _)| *players <= dealt)
+            .max_by_key(|(players, _)| *players)
+            .map(|(_, cap)| *cap)
+    }
+
+    // more than one player was left in when the preflop closed
+    // the flop is always dealt, even when everyone folds to one player, so it cant tell on its own
+    pub(crate) fn saw_flop(&self) -> bool {
+        let folds = self.ah.pf.iter().filter(|i| self.ah.actions[**i].t == ActionType::Fold).count();
+        self.flop[0].value != 0 && self.ah.start_bal.len() - folds > 1
+    }
+
+    // the rake owed on the pot as it is now, never more than the pot
+    pub fn rake_due(&self) -> u16 {
+        if self.config.no_flop_no_drop && !self.saw_flop() {
+            return 0;
+        }
+        let rake = (self.pot as f32 * self.config.rake_percent / 100.0).floor() as u16;
+        match self.rake_cap() {
+            Some(cap) => rake.min(cap),
+            None => rake,
+        }.min(self.pot)
+    }
+
+    // takes the rake out of the pot, returns the pots with it taken off, main pot first
+    pub(crate) fn take_rake(&mut self, mut pots: Vec<SidePot>) -> Vec<SidePot> {
+        let rake = self.rake_due();
+        if rake == 0 {
+            return pots;
+        }
+        self.pot -= rake;
+        self.rake = rake;
+        let mut left = rake;
+        for pot in &mut pots {
+            let amt = left.min(pot.value);
+            pot.value -= amt;
+            left -= amt;
+        }
+        pots.retain(|pot| pot.value > 0);
+        self.emit(DealerEvent::RakeTaken { amt: rake });
+        pots
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::*;
+    use crate::error::*;
+    use crate::player::*;
+
+    fn raked_dealer(p: Vec<Player>) -> Dealer {
+        let config = TableConfig {
+            rake_percent: 10.0,
+            rake_caps: vec![(2, 1), (3, 3)],
+            ..TableConfig::default()
+        };
+        Dealer::with_config(123, p, config)
+    }
+
+    fn call_down(dealer: &mut Dealer) {
+        while dealer.stage.is_betting() {
+            let seat = dealer.curr;
+            let t = if dealer.get_available_actions().contains(&ActionType::Check) {
+                ActionType::Check
+            } else if dealer.get_available_actions().contains(&ActionType::Call) {
+                ActionType::Call
+            } else {
+                ActionType::CallAI
+            };
+            dealer.p_action(Action { seat, t, value: 0 });
+        }
+    }
+
+    #[test]
+    fn test_rake() {
+        let mut dealer = raked_dealer((1..=3).map(|s| Player::new(s, 20)).collect());
+        dealer.new_hand();
+        dealer.p_action(Action { seat: 1, t: ActionType::Raise, value: 5 });
+        call_down(&mut dealer);
+        assert_eq!(dealer.pot, 21);
+        assert_eq!(dealer.rake_cap(), Some(3));
+        assert_eq!(dealer.rake_due(), 2);
+        let result = dealer.handle_showdown();
+        assert_eq!(result.rake, 2);
+        assert_eq!(result.pots.iter().map(|p| p.value).sum::<u16>(), 19);
+        assert_eq!(dealer.p.iter().map(|p| p.chips).sum::<u16>(), 58);
+        assert!(dealer.drain_events().contains(&DealerEvent::RakeTaken { amt: 2 }));
+        assert!(dealer.hand_history().lines().any(|l| l == "rake: 2"));
+    }
+
+    #[test]
+    fn test_rake_percent_out_of_range() {
+        for bad in [100.5, 250.0, -1.0, f32::NAN] {
+            let config = TableConfig { rake_percent: bad, ..TableConfig::default() };
+            assert_eq!(config.validate(), Err(DealerError::InvalidConfig("rake_percent")));
+        }
+        let config = TableConfig { rake_percent: 100.0, no_flop_no_drop: false, ..TableConfig::default() };
+        assert_eq!(config.validate(), Ok(()));
+        let mut dealer = Dealer::with_config(123, (1..=3).map(|s| Player::new(s, 20)).collect(), config);
+        dealer.new_hand();
+        call_down(&mut dealer);
+        assert_eq!(dealer.rake_due(), dealer.pot);
+        // set past the check, the rake still stops at the pot
+        dealer.config.rake_percent = 250.0;
+        assert_eq!(dealer.rake_due(), dealer.pot);
+        let result = dealer.handle_showdown();
+        assert_eq!(result.rake, 6);
+        assert_eq!(dealer.pot, 0);
+        assert_eq!(dealer.p.iter().map(|p| p.chips).sum::<u16>(), 54);
+    }
+
+    #[test]
+    #[should_panic(expected = "with_config invalid table config rake_percent")]
+    fn test_with_config_bad_rake() {
+        let config = TableConfig { rake_percent: 101.0, ..TableConfig::default() };
+        Dealer::with_config(123, (1..=3).map(|s| Player::new(s, 20)).collect(), config);
+    }
+
+    #[test]
+    fn test_rake_cap_heads_up() {
+        let mut dealer = raked_dealer(vec![Player::new(1, 50), Player::new(2, 50)]);
+        dealer.new_hand();
+        dealer.p_action(Action { seat: 1, t: ActionType::Raise, value: 4 });
+        call_down(&mut dealer);
+        assert_eq!(dealer.rake_cap(), Some(1));
+        let result = dealer.handle_showdown();
+        assert_eq!(result.rake, 1);
+        assert_eq!(dealer.p.iter().map(|p| p.chips).sum::<u16>(), 99);
+    }
+
+    #[test]
+    fn test_no_flop_no_drop() {
+        let mut dealer = raked_dealer((1..=3).map(|s| Player::new(s, 20)).collect());
+        dealer.new_hand();
+        dealer.p_action(Action { seat: 1, t: ActionType::Raise, value: 5 });
+        dealer.p_action(Action { seat: 2, t: ActionType::Call, value: 0 });
+        dealer.p_action(Action { seat: 3, t: ActionType::RaiseAI, value: 13 });
+        dealer.p_action(Action { seat: 1, t: ActionType::Fold, value: 0 });
+        dealer.p_action(Action { seat: 2, t: ActionType::Fold, value: 0 });
+        assert_eq!(dealer.pot, 21);
+        let result = dealer.handle_showdown();
+        assert_eq!(result.rake, 0);
+        assert_eq!(dealer.p.iter().map(|p| p.chips).sum::<u16>(), 60);
+
+        // with it off the pot is raked even without a flop
+        dealer.config.no_flop_no_drop = false;
+        dealer.new_hand();
+        dealer.p_action(Action { seat: 1, t: ActionType::Raise, value: 5 });
+        dealer.p_action(Action { seat: 2, t: ActionType::Call, value: 0 });
+        dealer.p_action(Action { seat: 3, t: ActionType::Raise, value: 6 });
+        dealer.p_action(Action { seat: 1, t: ActionType::Fold, value: 0 });
+        dealer.p_action(Action { seat: 2, t: ActionType::Fold, value: 0 });
+        assert_eq!(dealer.pot, 21);
+        assert_eq!(dealer.handle_showdown().rake, 2);
+        assert_eq!(dealer.p.iter().map(|p| p.chips).sum::<u16>(), 58);
+    }
+
+    #[test]
+    fn test_rake_side_pots() {
+        let mut dealer = raked_dealer(vec![
+            Player::new(1, 40),
+            Player::new(2, 1),
+            Player::new(3, 40),
+        ]);
+        dealer.new_hand();
+        dealer.p_action(Action { seat: 1, t: ActionType::Raise, value: 3 });
+        call_down(&mut dealer);
+        // the sb is all in for 1, the main pot is 3 and the side pot 8
+        assert_eq!(dealer.pot, 11);
+        let result = dealer.handle_showdown();
+        assert_eq!(result.rake, 1);
+        assert_eq!(result.pots.iter().map(|p| p.value).collect::<Vec<u16>>(), vec![2, 8]);
+        assert_eq!(dealer.p.iter().map(|p| p.chips).sum::<u16>(), 80);
+    }
+}
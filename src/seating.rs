@@ -0,0 +1,276 @@
+use crate::dealer::*;
+use crate::error::*;
+use crate::player::*;
+
+// seating for cash tables, players come and go between hands
+// a player sitting out keeps the seat and chips but isnt dealt in, see blinds.rs for the blinds they miss
+// a player that busts is sat out by new_hand until it has chips again
+// the button and blinds keep moving with move_button, a seat that empties can hold a dead button or sb
+// stacks only change between hands here, every change goes in the session log with the stack after it
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ChipChange {
+    BuyIn,
+    TopUp,
+    Rebuy,
+    CashOut,
+}
+
+// hands is how many hands had been dealt when the change was made
+#[derive(Debug, Clone, PartialEq)]
+pub struct SessionEntry {
+    pub hands: u32,
+    pub seat: u8,
+    pub change: ChipChange,
+    pub amt: u16,
+    pub chips: u16,
+}
+
+impl Dealer {
+    // Ok when no hand is being played, players can only sit down or stand up then
+    pub fn check_between_hands(&self) -> Result<(), DealerError> {
+        match self.stage {
+            Stages::WaitingForHand | Stages::Settled => Ok(()),
+            Stages::AwaitingSettlement => Err(DealerError::NotSettled),
+            _ => Err(DealerError::HandNotOver),
+        }
+    }
+
+    fn record(&mut self, seat: u8, change: ChipChange, amt: u16, chips: u16) {
+        self.session.push(SessionEntry { hands: self.hands, seat, change, amt, chips });
+    }
+
+    fn check_buy_in(&self, chips: u16) -> Result<(), DealerError> {
+        let (min, max) = (self.config.min_buy_in, self.config.max_buy_in);
+        if chips < min || chips > max {
+            return Err(DealerError::BuyInOutOfRange { min, max });
+        }
+        Ok(())
+    }
+
+    // seats a new player at an empty seat, seats start at 1, chips has to be in the buy in range
+    // once hands have been dealt the player owes a bb before it can play, posted live like a bb in any seat
+    pub fn seat_player(&mut self, seat: u8, chips: u16) -> Result<(), DealerError> {
+        self.check_between_hands()?;
+        if seat == 0 {
+            return Err(DealerError::InvalidValue);
+        }
+        if self.p.iter().any(|p| p.seat == seat) {
+            return Err(DealerError::SeatTaken(seat));
+        }
+        if chips < 1 {
+            return Err(DealerError::NotEnoughChips(seat));
+        }
+        self.check_buy_in(chips)?;
+        let mut player = Player::new(seat, chips);
+        player.missed_bb = self.bb != 0;
+        player.time_bank = self.config.time_bank;
+        self.p.push(player);
+        self.record(seat, ChipChange::BuyIn, chips, chips);
+        Ok(())
+    }
+
+    // takes the player off the table and gives it back with its chips
+    pub fn stand_player(&mut self, seat: u8) -> Result<Player, DealerError> {
+        self.check_between_hands()?;
+        let i = self.p.iter().position(|p| p.seat == seat).ok_or(DealerError::SeatNotFound(seat))?;
+        let player = self.p.remove(i);
+        self.record(seat, ChipChange::CashOut, player.chips, 0);
+        Ok(player)
+    }
+
+    // adds chips to a stack that isnt busted, up to the max buy in
+    pub fn top_up(&mut self, seat: u8, amt: u16) -> Result<(), DealerError> {
+        self.check_between_hands()?;
+        let chips = self.p.iter().find(|p| p.seat == seat).ok_or(DealerError::SeatNotFound(seat))?.chips;
+        if amt < 1 {
+            return Err(DealerError::InvalidValue);
+        }
+        if chips < 1 {
+            // busted, rebuy instead
+            return Err(DealerError::IllegalAction);
+        }
+        let max = self.config.max_buy_in;
+        if chips.checked_add(amt).is_none_or(|total| total > max) {
+            return Err(DealerError::BuyInOutOfRange { min: self.config.min_buy_in, max });
+        }
+        let player = self.p.iter_mut().find(|p| p.seat == seat).unwrap();
+        player.add_chips(&amt);
+        let chips = player.chips;
+        self.record(seat, ChipChange::TopUp, amt, chips);
+        Ok(())
+    }
+
+    // buys a busted player back in with a new stack in the buy in range and deals it back in
+    pub fn rebuy(&mut self, seat: u8, amt: u16) -> Result<(), DealerError> {
+        self.check_between_hands()?;
+        let chips = self.p.iter().find(|p| p.seat == seat).ok_or(DealerError::SeatNotFound(seat))?.chips;
+        if chips > 0 {
+            // still has chips, top up instead
+            return Err(DealerError::IllegalAction);
+        }
+        self.check_buy_in(amt)?;
+        let player = self.p.iter_mut().find(|p| p.seat == seat).unwrap();
+        player.add_chips(&amt);
+        if player.chips > player.owed_blinds(&self.config) {
+            player.sitting_out = false;
+        }
+        self.record(seat, ChipChange::Rebuy, amt, amt);
+        Ok(())
+    }
+
+    // the player isnt dealt in from the next hand, a hand it is in plays out as normal
+    pub fn sit_out(&mut self, seat: u8) -> Result<(), DealerError> {
+        let player = self.p.iter_mut().find(|p| p.seat == seat).ok_or(DealerError::SeatNotFound(seat))?;
+        player.sitting_out = true;
+        Ok(())
+    }
+
+    // deals the player back in from the next hand, it posts any blinds it missed
+    pub fn sit_in(&mut self, seat: u8) -> Result<(), DealerError> {
+        let player = self.p.iter_mut().find(|p| p.seat == seat).ok_or(DealerError::SeatNotFound(seat))?;
+        if player.chips <= player.owed_blinds(&self.config) {
+            return Err(DealerError::NotEnoughChips(seat));
+        }
+        player.sitting_out = false;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::*;
+
+    fn fold_round(dealer: &mut Dealer) {
+        while dealer.stage.is_betting() {
+            let seat = dealer.curr;
+            dealer.p_action(Action { seat, t: ActionType::Fold, value: 0 });
+        }
+        dealer.handle_showdown();
+    }
+
+    #[test]
+    fn test_seat_and_stand() {
+        let mut dealer = Dealer::new(123, vec![
+            Player::new(1, 50),
+            Player::new(2, 50),
+        ]);
+        assert_eq!(dealer.seat_player(2, 50), Err(DealerError::SeatTaken(2)));
+        assert_eq!(dealer.seat_player(0, 50), Err(DealerError::InvalidValue));
+        assert_eq!(dealer.seat_player(5, 0), Err(DealerError::NotEnoughChips(5)));
+        dealer.seat_player(5, 50).unwrap();
+        // nothing dealt yet, the new player doesnt owe a bb
+        assert!(!dealer.p.iter().find(|p| p.seat == 5).unwrap().missed_bb);
+        dealer.new_hand();
+        assert_eq!((dealer.button, dealer.sb, dealer.bb), (1, 2, 5));
+        assert_eq!(dealer.seat_player(3, 50), Err(DealerError::HandNotOver));
+        assert_eq!(dealer.stand_player(1).map(|p| p.seat), Err(DealerError::HandNotOver));
+        fold_round(&mut dealer);
+
+        // the bb stands up, the sb is dead on the empty seat next hand
+        let player = dealer.stand_player(5).unwrap();
+        assert_eq!(player.chips, 51);
+        assert_eq!(dealer.stand_player(5).map(|p| p.seat), Err(DealerError::SeatNotFound(5)));
+        dealer.seat_player(3, 40).unwrap();
+        dealer.move_button();
+        dealer.new_hand();
+        assert_eq!((dealer.button, dealer.sb, dealer.bb), (2, 5, 1));
+        assert!(!dealer.sb_live());
+        // seat 3 posts a bb to get in, it counts towards the call
+        assert_eq!(dealer.p.iter().find(|p| p.seat == 3).unwrap().chips, 40 - BIG_BLIND);
+        assert_eq!(dealer.committed.iter().find(|c| c.seat == 3).unwrap().dead, 0);
+        assert_eq!(dealer.pot, BIG_BLIND * 2);
+        assert_eq!(dealer.curr, 2);
+        dealer.p_action(Action { seat: 2, t: ActionType::Call, value: 0 });
+        assert_eq!(dealer.curr, 3);
+        assert!(dealer.get_available_actions().contains(&ActionType::Check));
+        dealer.p_action(Action { seat: 3, t: ActionType::Check, value: 0 });
+        dealer.p_action(Action { seat: 1, t: ActionType::Check, value: 0 });
+        assert_eq!(dealer.stage, Stages::Flop);
+        assert_eq!(dealer.pot, BIG_BLIND * 3);
+        fold_round(&mut dealer);
+        dealer.move_button();
+        dealer.new_hand();
+        assert_eq!((dealer.button, dealer.sb, dealer.bb), (5, 1, 2));
+        assert_eq!(dealer.ah.start_bal.iter().map(|sb| sb.seat).collect::<Vec<u8>>(), vec![1, 2, 3]);
+    }
+
+    #[test]
+    fn test_sit_out() {
+        let mut dealer = Dealer::new(123, (1..=3).map(|s| Player::new(s, 50)).collect());
+        assert_eq!(dealer.sit_out(4), Err(DealerError::SeatNotFound(4)));
+        dealer.sit_out(2).unwrap();
+        dealer.new_hand();
+        // heads up between 1 and 3
+        assert_eq!((dealer.button, dealer.sb, dealer.bb), (1, 1, 3));
+        assert!(dealer.p.iter().find(|p| p.seat == 2).unwrap().is_folded);
+        // sitting out mid hand only counts from the next hand
+        dealer.sit_out(3).unwrap();
+        assert!(!dealer.p.iter().find(|p| p.seat == 3).unwrap().is_folded);
+        fold_round(&mut dealer);
+        assert_eq!(dealer.try_new_hand(), Err(DealerError::NotEnoughPlayers));
+        dealer.sit_in(2).unwrap();
+        dealer.sit_in(3).unwrap();
+        dealer.try_new_hand().unwrap();
+        assert_eq!(dealer.ah.start_bal.len(), 3);
+    }
+
+    #[test]
+    fn test_busted_player_sits_out() {
+        let mut dealer = Dealer::new(123, (1..=3).map(|s| Player::new(s, 50)).collect());
+        dealer.new_hand();
+        fold_round(&mut dealer);
+        dealer.p.iter_mut().find(|p| p.seat == 2).unwrap().chips = 0;
+        dealer.move_button();
+        dealer.try_new_hand().unwrap();
+        let player = dealer.p.iter().find(|p| p.seat == 2).unwrap();
+        assert!(player.sitting_out);
+        assert!(player.is_folded);
+        assert_eq!(dealer.ah.start_bal.len(), 2);
+        assert_eq!(dealer.sit_in(2), Err(DealerError::NotEnoughChips(2)));
+    }
+
+    #[test]
+    fn test_buy_in_limits() {
+        let config = TableConfig { min_buy_in: 20, max_buy_in: 100, ..TableConfig::default() };
+        let mut dealer = Dealer::with_config(123, vec![
+            Player::new(1, 50),
+            Player::new(2, 50),
+        ], config);
+        assert_eq!(dealer.seat_player(3, 10), Err(DealerError::BuyInOutOfRange { min: 20, max: 100 }));
+        assert_eq!(dealer.seat_player(3, 101), Err(DealerError::BuyInOutOfRange { min: 20, max: 100 }));
+        dealer.seat_player(3, 100).unwrap();
+        assert_eq!(dealer.top_up(3, 1), Err(DealerError::BuyInOutOfRange { min: 20, max: 100 }));
+        assert_eq!(dealer.top_up(1, 0), Err(DealerError::InvalidValue));
+        dealer.top_up(1, 50).unwrap();
+        assert_eq!(dealer.p.iter().find(|p| p.seat == 1).unwrap().chips, 100);
+        assert_eq!(dealer.rebuy(2, 50), Err(DealerError::IllegalAction));
+
+        dealer.new_hand();
+        assert_eq!(dealer.top_up(2, 10), Err(DealerError::HandNotOver));
+        fold_round(&mut dealer);
+
+        dealer.p.iter_mut().find(|p| p.seat == 2).unwrap().chips = 0;
+        dealer.move_button();
+        dealer.new_hand();
+        fold_round(&mut dealer);
+        assert!(dealer.p.iter().find(|p| p.seat == 2).unwrap().sitting_out);
+        assert_eq!(dealer.top_up(2, 50), Err(DealerError::IllegalAction));
+        assert_eq!(dealer.rebuy(2, 10), Err(DealerError::BuyInOutOfRange { min: 20, max: 100 }));
+        dealer.rebuy(2, 40).unwrap();
+        assert!(!dealer.p.iter().find(|p| p.seat == 2).unwrap().sitting_out);
+        let chips = dealer.stand_player(3).unwrap().chips;
+
+        assert_eq!(dealer.session.iter().map(|e| (e.hands, e.seat, e.change.clone())).collect::<Vec<_>>(), vec![
+            (0, 1, ChipChange::BuyIn),
+            (0, 2, ChipChange::BuyIn),
+            (0, 3, ChipChange::BuyIn),
+            (0, 1, ChipChange::TopUp),
+            (2, 2, ChipChange::Rebuy),
+            (2, 3, ChipChange::CashOut),
+        ]);
+        assert_eq!(dealer.session[3].chips, 100);
+        assert_eq!(dealer.session[5].amt, chips);
+    }
+}
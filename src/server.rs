@@ -0,0 +1,575 @@
+use std::collections::HashMap;
+use std::io;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use futures_util::{SinkExt, StreamExt};
+use serde::{Deserialize, Serialize};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::mpsc::{unbounded_channel, UnboundedSender};
+use tokio_tungstenite::tungstenite::Message;
+
+use rs_handstrength::Card;
+
+use crate::dealer::*;
+use crate::deck::*;
+use crate::player::*;
+use crate::view::*;
+use crate::advance::*;
+
+// websocket server hosting many dealer tables, built with the server feature
+// every websocket text message is one json object tagged by "type"
+//
+// client -> server
+//   {"type":"join","table":1,"seat":2}
+//   {"type":"leave","table":1}                      sits the seat out, mid hand it checks or folds every turn
+//   {"type":"start","table":1}                      deals the next hand
+//   {"type":"action","table":1,"t":"R","value":5}   t is an ActionType code, value defaults to 0
+//   {"type":"advance","table":1,"advance":"call","value":2}
+//                                                   check_fold, check, call, call_any or fold_to_any_bet, "" cancels
+// server -> client
+//   {"type":"joined","table":1,"seat":2}
+//   {"type":"state",...}                            see SeatState, sent to every seated client after each change
+//   {"type":"hand_over","table":1,"chips":[...],"shown":[{"seat":2,"hand":["As","Kh","Qd","Jc"]},...]}
+//   {"type":"error","message":"..."}
+//
+// actions go through Dealer::try_p_action for the client's own seat, so turn order and bet sizes
+// are enforced by the dealer and rejected actions come back as errors
+
+pub type ClientId = u64;
+
+// cards of players still in at showdown are shown to the table
+pub const REVEAL_POLICY: RevealPolicy = RevealPolicy::Showdown;
+
+// how often serve checks the action clocks, tables without TableConfig.action_time never time out
+pub const CLOCK_TICK: Duration = Duration::from_millis(100);
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ClientMsg {
+    Join { table: u32, seat: u8 },
+    Leave { table: u32 },
+    Start { table: u32 },
+    Action {
+        table: u32,
+        t: String,
+        #[serde(default)]
+        value: u16,
+    },
+    Advance {
+        table: u32,
+        advance: String,
+        #[serde(default)]
+        value: u16,
+    },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct SeatChips {
+    pub seat: u8,
+    pub chips: u16,
+    pub is_folded: bool,
+    pub is_all_in: bool,
+}
+
+// hole cards shown at showdown
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ShownHand {
+    pub seat: u8,
+    pub hand: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct SeatAction {
+    pub seat: u8,
+    pub t: String,
+    pub value: u16,
+}
+
+// a table as seen from one seat, built from Dealer::view_for so only that seat's hole cards are included
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct SeatState {
+    pub table: u32,
+    pub seat: u8,
+    pub stage: String,
+    pub hand: Vec<String>,
+    pub flop: Vec<String>,
+    pub button: u8,
+    pub curr: u8,
+    pub pot: u16,
+    pub players: Vec<SeatChips>,
+    pub actions: Vec<SeatAction>,
+    pub legal: Vec<String>,
+    // the seat's queued advance action, empty when there is none
+    pub advance: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ServerMsg {
+    Joined { table: u32, seat: u8 },
+    State {
+        #[serde(flatten)]
+        state: SeatState,
+    },
+    HandOver { table: u32, chips: Vec<SeatChips>, shown: Vec<ShownHand> },
+    Error { message: String },
+}
+
+fn seat_chips(view: &TableView) -> Vec<SeatChips> {
+    view.players.iter().map(|p| SeatChips {
+        seat: p.seat,
+        chips: p.chips,
+        is_folded: p.is_folded,
+        is_all_in: p.is_all_in,
+    }).collect()
+}
+
+fn parse_advance(name: &str, value: u16) -> Result<Option<AdvanceAction>, String> {
+    match name {
+        "" => Ok(None),
+        "check_fold" => Ok(Some(AdvanceAction::CheckFold)),
+        "check" => Ok(Some(AdvanceAction::Check)),
+        "call" => Ok(Some(AdvanceAction::Call(value))),
+        "call_any" => Ok(Some(AdvanceAction::CallAny)),
+        "fold_to_any_bet" => Ok(Some(AdvanceAction::FoldToAnyBet)),
+        _ => Err(format!("unknown advance action {}", name)),
+    }
+}
+
+fn advance_name(advance: &AdvanceAction) -> String {
+    match advance {
+        AdvanceAction::CheckFold => "check_fold",
+        AdvanceAction::Check => "check",
+        AdvanceAction::Call(_) => "call",
+        AdvanceAction::CallAny => "call_any",
+        AdvanceAction::FoldToAnyBet => "fold_to_any_bet",
+    }.to_string()
+}
+
+fn cards_to_strings(cards: &[Card]) -> Vec<String> {
+    cards.iter().map(card_to_string).collect()
+}
+
+pub struct Table {
+    pub id: u32,
+    pub dealer: Dealer,
+    pub hands: u32,
+    // hand in progress, false before the first hand and after each showdown
+    pub in_hand: bool,
+    clients: HashMap<u8, (ClientId, UnboundedSender<ServerMsg>)>,
+}
+
+impl Table {
+    pub fn new(id: u32, seed: u64, stacks: &[u16]) -> Self {
+        let p = stacks.iter().enumerate().map(|(i, c)| Player::new(i as u8 + 1, *c)).collect::<Vec<Player>>();
+        Table { id, dealer: Dealer::new(seed, p), hands: 0, in_hand: false, clients: HashMap::new() }
+    }
+
+    pub fn seat_state(&self, seat: u8) -> SeatState {
+        let view = self.dealer.view_for(seat, &REVEAL_POLICY);
+        SeatState {
+            table: self.id,
+            seat,
+            stage: format!("{:?}", view.stage),
+            hand: view.player(seat).and_then(|p| p.hand).map(|h| cards_to_strings(&h)).unwrap_or_default(),
+            flop: view.flop.map(|f| cards_to_strings(&f)).unwrap_or_default(),
+            button: view.button,
+            curr: view.curr,
+            pot: view.pot,
+            players: seat_chips(&view),
+            actions: view.actions.iter().map(|a| SeatAction { seat: a.seat, t: a.t.to_string(), value: a.value }).collect(),
+            legal: view.legal.iter().map(|t| t.to_string()).collect(),
+            advance: self.dealer.queued_advance(seat).map(advance_name).unwrap_or_default(),
+        }
+    }
+
+    fn broadcast_state(&self) {
+        for (seat, (_, tx)) in &self.clients {
+            let _ = tx.send(ServerMsg::State { state: self.seat_state(*seat) });
+        }
+    }
+
+    fn seat_of(&self, client: ClientId) -> Option<u8> {
+        self.clients.iter().find(|(_, (c, _))| *c == client).map(|(seat, _)| *seat)
+    }
+
+    fn start(&mut self) -> Result<(), String> {
+        if self.in_hand {
+            return Err("hand in progress".to_string());
+        }
+        if self.hands > 0 {
+            self.dealer.move_button();
+        }
+        self.dealer.try_new_hand().map_err(|e| e.to_string())?;
+        self.in_hand = true;
+        self.hands += 1;
+        Ok(())
+    }
+
+    fn act(&mut self, seat: u8, t: &str, value: u16) -> Result<(), String> {
+        if !self.in_hand {
+            return Err("no hand in progress".to_string());
+        }
+        let t = t.parse::<ActionType>()?;
+        self.dealer.try_p_action(Action { seat, t, value }).map_err(|e| e.to_string())?;
+        self.settle_if_over();
+        Ok(())
+    }
+
+    fn advance(&mut self, seat: u8, name: &str, value: u16) -> Result<(), String> {
+        if !self.in_hand {
+            return Err("no hand in progress".to_string());
+        }
+        match parse_advance(name, value)? {
+            Some(advance) => self.dealer.queue_advance(seat, advance).map_err(|e| e.to_string()),
+            None => {
+                self.dealer.cancel_advance(seat);
+                Ok(())
+            }
+        }
+    }
+
+    // frees the seat and sits it out so it isnt dealt the next hand
+    // mid hand the seat is folded if it is curr, otherwise it checks or folds every turn until the hand ends
+    fn leave(&mut self, seat: u8) {
+        self.clients.remove(&seat);
+        let _ = self.dealer.sit_out(seat);
+        if !self.in_hand {
+            return;
+        }
+        if self.dealer.curr == seat && self.dealer.stage.is_betting() {
+            let t = if self.dealer.get_available_actions().contains(&ActionType::Check) {
+                ActionType::Check
+            } else {
+                ActionType::Fold
+            };
+            let _ = self.dealer.try_p_action(Action { seat, t, value: 0 });
+        }
+        // folded and all in seats have nothing left to do, the queue rejects them
+        let _ = self.dealer.queue_advance(seat, AdvanceAction::FoldToAnyBet);
+        self.settle_if_over();
+    }
+
+    // acts for a seat that ran out of time, true when it did
+    fn poll_clock(&mut self) -> bool {
+        if !self.in_hand || self.dealer.poll_clock().is_none() {
+            return false;
+        }
+        self.settle_if_over();
+        true
+    }
+
+    fn settle_if_over(&mut self) {
+        if self.dealer.stage == Stages::AwaitingSettlement {
+            self.dealer.handle_showdown();
+            self.in_hand = false;
+            let shown = self.dealer.shown_hands(&REVEAL_POLICY).iter()
+                .map(|(seat, hand)| ShownHand { seat: *seat, hand: cards_to_strings(hand) })
+                .collect();
+            let msg = ServerMsg::HandOver {
+                table: self.id,
+                chips: seat_chips(&self.dealer.spectator_view()),
+                shown,
+            };
+            for (_, tx) in self.clients.values() {
+                let _ = tx.send(msg.clone());
+            }
+        }
+    }
+}
+
+pub struct Server {
+    tables: Mutex<HashMap<u32, Table>>,
+    next_client: AtomicU64,
+}
+
+impl Server {
+    pub fn new(tables: Vec<Table>) -> Self {
+        Server {
+            tables: Mutex::new(tables.into_iter().map(|t| (t.id, t)).collect()),
+            next_client: AtomicU64::new(1),
+        }
+    }
+
+    // handles one client message, errors go back to the client only
+    pub fn handle(&self, client: ClientId, tx: &UnboundedSender<ServerMsg>, msg: ClientMsg) {
+        if let Err(message) = self.try_handle(client, tx, msg) {
+            let _ = tx.send(ServerMsg::Error { message });
+        }
+    }
+
+    fn try_handle(&self, client: ClientId, tx: &UnboundedSender<ServerMsg>, msg: ClientMsg) -> Result<(), String> {
+        let mut tables = self.tables.lock().unwrap();
+        let table_id = match &msg {
+            ClientMsg::Join { table, .. } | ClientMsg::Leave { table } | ClientMsg::Start { table }
+                | ClientMsg::Action { table, .. } | ClientMsg::Advance { table, .. } => *table,
+        };
+        let table = tables.get_mut(&table_id).ok_or(format!("table {} not found", table_id))?;
+        match msg {
+            ClientMsg::Join { seat, .. } => {
+                if !table.dealer.p.iter().any(|p| p.seat == seat) {
+                    return Err(format!("seat {} not found", seat));
+                }
+                if table.clients.contains_key(&seat) {
+                    return Err(format!("seat {} taken", seat));
+                }
+                if table.seat_of(client).is_some() {
+                    return Err("already seated at this table".to_string());
+                }
+                table.clients.insert(seat, (client, tx.clone()));
+                // a seat left by an earlier client is dealt back in if it still has the chips
+                let _ = table.dealer.sit_in(seat);
+                let _ = tx.send(ServerMsg::Joined { table: table_id, seat });
+                let _ = tx.send(ServerMsg::State { state: table.seat_state(seat) });
+            }
+            ClientMsg::Leave { .. } => {
+                let seat = table.seat_of(client).ok_or("not seated at this table")?;
+                let in_hand = table.in_hand;
+                table.leave(seat);
+                if in_hand {
+                    table.broadcast_state();
+                }
+            }
+            ClientMsg::Start { .. } => {
+                table.seat_of(client).ok_or("not seated at this table")?;
+                table.start()?;
+                table.broadcast_state();
+            }
+            ClientMsg::Action { t, value, .. } => {
+                let seat = table.seat_of(client).ok_or("not seated at this table")?;
+                table.act(seat, &t, value)?;
+                table.broadcast_state();
+            }
+            ClientMsg::Advance { advance, value, .. } => {
+                let seat = table.seat_of(client).ok_or("not seated at this table")?;
+                table.advance(seat, &advance, value)?;
+                let _ = tx.send(ServerMsg::State { state: table.seat_state(seat) });
+            }
+        }
+        Ok(())
+    }
+
+    // leaves every seat the client held, see Table::leave
+    pub fn disconnect(&self, client: ClientId) {
+        let mut tables = self.tables.lock().unwrap();
+        for table in tables.values_mut() {
+            if let Some(seat) = table.seat_of(client) {
+                let in_hand = table.in_hand;
+                table.leave(seat);
+                if in_hand {
+                    table.broadcast_state();
+                }
+            }
+        }
+    }
+
+    // acts for every seat whose action clock ran out, serve calls it on a timer
+    pub fn poll_clocks(&self) {
+        let mut tables = self.tables.lock().unwrap();
+        for table in tables.values_mut() {
+            if table.poll_clock() {
+                table.broadcast_state();
+            }
+        }
+    }
+
+    pub fn with_table<R>(&self, id: u32, f: impl FnOnce(&Table) -> R) -> Option<R> {
+        self.tables.lock().unwrap().get(&id).map(f)
+    }
+}
+
+async fn handle_conn(server: Arc<Server>, stream: TcpStream) {
+    let ws = match tokio_tungstenite::accept_async(stream).await {
+        Ok(ws) => ws,
+        Err(_) => return,
+    };
+    let client = server.next_client.fetch_add(1, Ordering::Relaxed);
+    let (mut sink, mut source) = ws.split();
+    let (tx, mut rx) = unbounded_channel::<ServerMsg>();
+    let writer = tokio::spawn(async move {
+        while let Some(msg) = rx.recv().await {
+            let text = serde_json::to_string(&msg).unwrap();
+            if sink.send(Message::text(text)).await.is_err() {
+                break;
+            }
+        }
+    });
+    while let Some(Ok(msg)) = source.next().await {
+        match msg {
+            Message::Text(text) => match serde_json::from_str::<ClientMsg>(text.as_str()) {
+                Ok(msg) => server.handle(client, &tx, msg),
+                Err(e) => {
+                    let _ = tx.send(ServerMsg::Error { message: format!("bad message {}", e) });
+                }
+            },
+            Message::Close(_) => break,
+            _ => {}
+        }
+    }
+    server.disconnect(client);
+    drop(tx);
+    let _ = writer.await;
+}
+
+// accepts websocket connections until the listener fails
+pub async fn serve(listener: TcpListener, server: Arc<Server>) -> io::Result<()> {
+    let ticker = server.clone();
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(CLOCK_TICK);
+        loop {
+            interval.tick().await;
+            ticker.poll_clocks();
+        }
+    });
+    loop {
+        let (stream, _) = listener.accept().await?;
+        tokio::spawn(handle_conn(server.clone(), stream));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::*;
+    use tokio_tungstenite::{connect_async, MaybeTlsStream, WebSocketStream};
+
+    type Ws = WebSocketStream<MaybeTlsStream<TcpStream>>;
+
+    async fn send(ws: &mut Ws, msg: &ClientMsg) {
+        ws.send(Message::text(serde_json::to_string(msg).unwrap())).await.unwrap();
+    }
+
+    async fn recv(ws: &mut Ws) -> ServerMsg {
+        loop {
+            if let Message::Text(text) = ws.next().await.unwrap().unwrap() {
+                return serde_json::from_str(text.as_str()).unwrap();
+            }
+        }
+    }
+
+    // reads until a message matching f
+    async fn recv_until(ws: &mut Ws, f: impl Fn(&ServerMsg) -> bool) -> ServerMsg {
+        loop {
+            let msg = recv(ws).await;
+            if f(&msg) {
+                return msg;
+            }
+        }
+    }
+
+    async fn start_server() -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let server = Arc::new(Server::new(vec![
+            Table::new(1, 123, &[20, 20]),
+            Table::new(2, 123, &[20, 20, 20]),
+        ]));
+        tokio::spawn(serve(listener, server));
+        format!("ws://{}", addr)
+    }
+
+    #[tokio::test]
+    async fn test_two_clients_play_a_hand() {
+        let url = start_server().await;
+        let (mut a, _) = connect_async(&url).await.unwrap();
+        let (mut b, _) = connect_async(&url).await.unwrap();
+        send(&mut a, &ClientMsg::Join { table: 1, seat: 1 }).await;
+        assert_eq!(recv(&mut a).await, ServerMsg::Joined { table: 1, seat: 1 });
+        send(&mut b, &ClientMsg::Join { table: 1, seat: 1 }).await;
+        assert!(matches!(recv(&mut b).await, ServerMsg::Error { .. }));
+        send(&mut b, &ClientMsg::Join { table: 1, seat: 2 }).await;
+        assert_eq!(recv(&mut b).await, ServerMsg::Joined { table: 1, seat: 2 });
+
+        send(&mut a, &ClientMsg::Start { table: 1 }).await;
+        let state = match recv_until(&mut a, |m| matches!(m, ServerMsg::State { state } if state.stage == "PreFlop")).await {
+            ServerMsg::State { state } => state,
+            _ => unreachable!(),
+        };
+        // only our own cards are sent
+        assert_eq!(state.hand.len(), 4);
+        assert_eq!(state.players.len(), 2);
+        let curr = state.curr;
+        let (mut to_act, mut waiting) = if curr == 1 { (a, b) } else { (b, a) };
+
+        // acting out of turn is rejected by the dealer
+        send(&mut waiting, &ClientMsg::Action { table: 1, t: "F".to_string(), value: 0 }).await;
+        let err = recv_until(&mut waiting, |m| matches!(m, ServerMsg::Error { .. })).await;
+        assert!(matches!(err, ServerMsg::Error { message } if message.starts_with("not seat")));
+
+        send(&mut to_act, &ClientMsg::Action { table: 1, t: "F".to_string(), value: 0 }).await;
+        for ws in [&mut to_act, &mut waiting] {
+            let msg = recv_until(ws, |m| matches!(m, ServerMsg::HandOver { .. })).await;
+            if let ServerMsg::HandOver { chips, .. } = msg {
+                assert_eq!(chips.iter().map(|c| c.chips).sum::<u16>(), 40);
+            }
+        }
+    }
+
+    #[test]
+    fn test_poll_clocks() {
+        let clock = Arc::new(FakeClock::default());
+        let mut table = Table::new(1, 123, &[20, 20]);
+        table.dealer.config.action_time = Some(1000);
+        table.dealer.clock = clock.clone();
+        table.start().unwrap();
+        let server = Server::new(vec![table]);
+        server.poll_clocks();
+        assert_eq!(server.with_table(1, |t| t.in_hand), Some(true));
+        // heads up the sb acts first, it cant check and is folded, which ends the hand
+        clock.advance(1000);
+        server.poll_clocks();
+        assert_eq!(server.with_table(1, |t| (t.in_hand, t.dealer.stage.clone())), Some((false, Stages::Settled)));
+    }
+
+    #[test]
+    fn test_advance() {
+        let mut table = Table::new(1, 123, &[20, 20, 20]);
+        assert!(table.advance(2, "check_fold", 0).is_err());
+        table.start().unwrap();
+        assert_eq!(table.dealer.curr, 1);
+        assert!(table.advance(2, "raise", 0).is_err());
+        table.advance(2, "call", 1).unwrap();
+        assert_eq!(table.seat_state(2).advance, "call");
+        table.advance(3, "check", 0).unwrap();
+        table.advance(3, "", 0).unwrap();
+        assert_eq!(table.seat_state(3).advance, "");
+        // seat 2 completes the sb as soon as seat 1 calls
+        table.act(1, "C", 0).unwrap();
+        assert_eq!(table.dealer.curr, 3);
+        assert_eq!(table.seat_state(2).advance, "");
+    }
+
+    #[test]
+    fn test_leave_mid_hand() {
+        let mut table = Table::new(1, 123, &[20, 20, 20]);
+        table.start().unwrap();
+        assert_eq!(table.dealer.curr, 1);
+        // curr is folded straight away
+        table.leave(1);
+        assert!(table.dealer.p.iter().find(|p| p.seat == 1).unwrap().is_folded);
+        assert_eq!(table.dealer.curr, 2);
+        // the bb checks or folds when its turn comes
+        table.leave(3);
+        assert_eq!(table.dealer.queued_advance(3), Some(&AdvanceAction::FoldToAnyBet));
+        table.act(2, "R", BIG_BLIND).unwrap();
+        assert!(!table.in_hand);
+        assert_eq!(table.dealer.stage, Stages::Settled);
+        assert_eq!(table.dealer.p.iter().find(|p| p.seat == 3).unwrap().chips, 20 - BIG_BLIND);
+        // both seats that left are sat out, so the next hand cant start
+        assert!(table.start().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_bad_messages() {
+        let url = start_server().await;
+        let (mut a, _) = connect_async(&url).await.unwrap();
+        a.send(Message::text("hello")).await.unwrap();
+        assert!(matches!(recv(&mut a).await, ServerMsg::Error { .. }));
+        send(&mut a, &ClientMsg::Join { table: 9, seat: 1 }).await;
+        assert_eq!(recv(&mut a).await, ServerMsg::Error { message: "table 9 not found".to_string() });
+        send(&mut a, &ClientMsg::Start { table: 2 }).await;
+        assert_eq!(recv(&mut a).await, ServerMsg::Error { message: "not seated at this table".to_string() });
+    }
+}
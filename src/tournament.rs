@@ -0,0 +1,237 @@
+use crate::config::*;
+use crate::dealer::*;
+use crate::error::*;
+use crate::player::*;
+
+// single table tournament run on a Dealer
+// the blinds and ante go up a level every so many hands, or every so many ms on the dealer's clock
+// players that bust are taken off the table, the ones out in the same hand are placed by the stacks they started it with
+//   levels 1/2, 2/4 ante 1, 5/10 ante 1, 10 hands a level  ->  hands 11 to 20 are played at 2/4 ante 1
+// the last level stays until the tournament is over
+// the button moves with the dead button rule from blinds.rs, so a bust can leave the sb or button dead
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct BlindLevel {
+    pub small_blind: u16,
+    pub big_blind: u16,
+    pub ante: u16,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum LevelPace {
+    // hands per level
+    Hands(u32),
+    // ms per level on Dealer.clock, counted from the first hand
+    Time(u64),
+}
+
+#[derive(Debug, Clone)]
+pub struct Tournament {
+    pub dealer: Dealer,
+    pub levels: Vec<BlindLevel>,
+    pub pace: LevelPace,
+    pub level: usize,          // index into levels the last hand was dealt at
+    pub started: Option<u64>,  // when the first hand was dealt, only with LevelPace::Time
+    pub busted: Vec<u8>,       // seats in the order they went out, first out first
+}
+
+impl Tournament {
+    pub fn new(seed: u64, p: Vec<Player>, levels: Vec<BlindLevel>, pace: LevelPace) -> Self {
+        Tournament::with_config(seed, p, levels, pace, TableConfig::default())
+    }
+
+    // the table rules in config, its blinds and ante are replaced by the levels
+    pub fn with_config(seed: u64, p: Vec<Player>, levels: Vec<BlindLevel>, pace: LevelPace, config: TableConfig) -> Self {
+        if levels.is_empty() {
+            panic!("Tournament No blind levels");
+        }
+        let mut tournament = Tournament {
+            dealer: Dealer::with_config(seed, p, config),
+            levels,
+            pace,
+            level: 0,
+            started: None,
+            busted: vec![],
+        };
+        tournament.set_level(0);
+        tournament
+    }
+
+    fn set_level(&mut self, level: usize) {
+        let l = &self.levels[level];
+        self.dealer.config.small_blind = l.small_blind;
+        self.dealer.config.big_blind = l.big_blind;
+        self.dealer.config.ante = l.ante;
+        self.level = level;
+    }
+
+    // the level the next hand is dealt at, levels never go back down
+    pub fn due_level(&self) -> usize {
+        let passed = match self.pace {
+            LevelPace::Hands(hands) => (self.dealer.hands / hands.max(1)) as usize,
+            LevelPace::Time(ms) => match self.started {
+                Some(started) => (self.dealer.clock.now().saturating_sub(started) / ms.max(1)) as usize,
+                None => 0,
+            },
+        };
+        passed.min(self.levels.len() - 1).max(self.level)
+    }
+
+    // one player has all the chips
+    pub fn is_over(&self) -> bool {
+        self.dealer.p.len() < 2
+    }
+
+    // seats from first place down, players still in by chips then the busted ones last out first
+    pub fn standings(&self) -> Vec<u8> {
+        let mut left = self.dealer.p.iter().map(|p| (p.seat, p.chips)).collect::<Vec<(u8, u16)>>();
+        left.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+        left.iter().map(|(seat, _)| *seat).chain(self.busted.iter().rev().copied()).collect()
+    }
+
+    // goes up to the due level, moves the button and deals the next hand
+    pub fn start_hand(&mut self) -> Result<(), DealerError> {
+        if self.is_over() {
+            return Err(DealerError::NotEnoughPlayers);
+        }
+        self.dealer.check_between_hands()?;
+        if self.started.is_none() && matches!(self.pace, LevelPace::Time(_)) {
+            self.started = Some(self.dealer.clock.now());
+        }
+        let level = self.due_level();
+        self.set_level(level);
+        if self.dealer.hands > 0 {
+            self.dealer.move_button();
+        }
+        self.dealer.try_new_hand()
+    }
+
+    // settles the hand and takes the players that busted off the table
+    pub fn finish_hand(&mut self) -> Result<ShowdownResult, DealerError> {
+        let result = self.dealer.try_handle_showdown()?;
+        let mut out = self.dealer.ah.start_bal.iter()
+            .filter(|sb| self.dealer.p.iter().any(|p| p.seat == sb.seat && p.chips == 0))
+            .cloned()
+            .collect::<Vec<StartingBal>>();
+        // the smaller stack goes out first
+        out.sort_by_key(|sb| sb.bal);
+        for sb in out {
+            self.dealer.stand_player(sb.seat)?;
+            self.busted.push(sb.seat);
+        }
+        Ok(result)
+    }
+
+    // plays a hand start to finish, decide picks the action for dealer.curr
+    pub fn play_hand(&mut self, decide: &mut impl FnMut(&Dealer) -> Action) -> Result<ShowdownResult, DealerError> {
+        self.start_hand()?;
+        while self.dealer.stage.is_betting() {
+            let action = decide(&self.dealer);
+            self.dealer.try_p_action(action)?;
+        }
+        self.finish_hand()
+    }
+
+    // plays hands until one player is left, returns the standings
+    pub fn run(&mut self, mut decide: impl FnMut(&Dealer) -> Action) -> Result<Vec<u8>, DealerError> {
+        while !self.is_over() {
+            self.play_hand(&mut decide)?;
+        }
+        Ok(self.standings())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+    use crate::clock::*;
+    use crate::events::*;
+
+    fn levels() -> Vec<BlindLevel> {
+        vec![
+            BlindLevel { small_blind: 1, big_blind: 2, ante: 0 },
+            BlindLevel { small_blind: 2, big_blind: 4, ante: 1 },
+            BlindLevel { small_blind: 5, big_blind: 10, ante: 2 },
+        ]
+    }
+
+    fn fold_or_check(dealer: &Dealer) -> Action {
+        let t = if dealer.get_available_actions().contains(&ActionType::Check) { ActionType::Check } else { ActionType::Fold };
+        Action { seat: dealer.curr, t, value: 0 }
+    }
+
+    // puts everything in whenever it can
+    fn shove(dealer: &Dealer) -> Action {
+        let seat = dealer.curr;
+        let chips = dealer.p.iter().find(|p| p.seat == seat).unwrap().chips;
+        let call_amt = dealer.get_call_amt(&seat);
+        let available = dealer.get_available_actions();
+        let (t, value) = if available.contains(&ActionType::RaiseAI) {
+            (ActionType::RaiseAI, chips - call_amt)
+        } else if available.contains(&ActionType::BetAI) {
+            (ActionType::BetAI, chips)
+        } else if available.contains(&ActionType::Raise) {
+            (ActionType::Raise, (call_amt + dealer.pot).min(chips - call_amt))
+        } else if available.contains(&ActionType::Bet) {
+            (ActionType::Bet, dealer.get_bet_range().1)
+        } else if available.contains(&ActionType::Call) {
+            (ActionType::Call, 0)
+        } else if available.contains(&ActionType::CallAI) {
+            (ActionType::CallAI, 0)
+        } else {
+            (ActionType::Check, 0)
+        };
+        Action { seat, t, value }
+    }
+
+    #[test]
+    fn test_levels_by_hands() {
+        let mut t = Tournament::new(123, (1..=3).map(|s| Player::new(s, 100)).collect(), levels(), LevelPace::Hands(2));
+        t.play_hand(&mut fold_or_check).unwrap();
+        t.play_hand(&mut fold_or_check).unwrap();
+        assert_eq!(t.level, 0);
+        t.start_hand().unwrap();
+        assert_eq!(t.level, 1);
+        assert_eq!(t.dealer.config.big_blind, 4);
+        // 2 and 4, then 3 antes
+        assert_eq!(t.dealer.pot, 3 + 2 + 4);
+        assert_eq!(t.dealer.drain_events().iter().filter(|e| matches!(e, DealerEvent::AntePosted { amt: 1, .. })).count(), 3);
+        assert_eq!(t.dealer.get_call_amt(&t.dealer.curr), 4);
+        assert_eq!(t.dealer.min_raise, 4);
+    }
+
+    #[test]
+    fn test_levels_by_time() {
+        let clock = Arc::new(FakeClock::default());
+        let mut t = Tournament::new(123, (1..=3).map(|s| Player::new(s, 100)).collect(), levels(), LevelPace::Time(1000));
+        t.dealer.clock = clock.clone();
+        clock.set(500);
+        t.play_hand(&mut fold_or_check).unwrap();
+        clock.advance(999);
+        t.play_hand(&mut fold_or_check).unwrap();
+        assert_eq!(t.level, 0);
+        clock.advance(1);
+        t.play_hand(&mut fold_or_check).unwrap();
+        assert_eq!(t.level, 1);
+        // a long break jumps levels, and the last one stays
+        clock.advance(10_000);
+        t.play_hand(&mut fold_or_check).unwrap();
+        assert_eq!(t.level, 2);
+        assert_eq!(t.dealer.config.big_blind, 10);
+    }
+
+    #[test]
+    fn test_run_to_the_end() {
+        let mut t = Tournament::new(123, (1..=4).map(|s| Player::new(s, 50)).collect(), levels(), LevelPace::Hands(5));
+        let standings = t.run(shove).unwrap();
+        assert!(t.is_over());
+        assert_eq!(t.busted.len(), 3);
+        let mut seats = standings.clone();
+        seats.sort();
+        assert_eq!(seats, vec![1, 2, 3, 4]);
+        assert_eq!(t.dealer.p[0].seat, standings[0]);
+        assert_eq!(t.dealer.p[0].chips, 200);
+        assert_eq!(t.start_hand(), Err(DealerError::NotEnoughPlayers));
+    }
+}
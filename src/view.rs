@@ -0,0 +1,223 @@
+use rs_handstrength::Card;
+use crate::dealer::*;
+
+// redacted views of the dealer, safe to send to a client
+// a view never has the deck or the seed, and only has the hole cards the viewer is allowed to see
+
+// which hole cards other than the viewer's own are shown once the hand is at showdown
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RevealPolicy {
+    // nobody else's cards, even at showdown
+    Never,
+    // players that did not fold, when more than one is left
+    Showdown,
+    // every dealt in player including the ones that folded, eg for practice tables
+    All,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct PlayerView {
+    pub seat: u8,
+    pub chips: u16,
+    pub is_folded: bool,
+    pub is_all_in: bool,
+    pub sitting_out: bool,
+    pub hand: Option<[Card; 4]>,
+}
+
+#[derive(Debug, Clone)]
+pub struct TableView {
+    // None for spectators
+    pub viewer: Option<u8>,
+    pub stage: Stages,
+    pub button: u8,
+    pub curr: u8,
+    pub pot: u16,
+    pub flop: Option<[Card; 3]>,
+    pub players: Vec<PlayerView>,
+    pub actions: Vec<Action>,
+    pub pf: Vec<usize>,
+    pub f: Vec<usize>,
+    // the viewer's call amount and available actions, 0 and empty when its not the viewer's turn
+    pub call_amt: u16,
+    pub legal: Vec<ActionType>,
+}
+
+impl TableView {
+    pub fn player(&self, seat: u8) -> Option<&PlayerView> {
+        self.players.iter().find(|p| p.seat == seat)
+    }
+}
+
+impl Dealer {
+    fn view(&self, viewer: Option<u8>, policy: &RevealPolicy) -> TableView {
+        let shown = if viewer.is_some() { self.shown_hands(policy) } else { vec![] };
+        let players = self.p.iter().map(|p| {
+            // players that were not dealt in have no cards to see
+            let in_hand = self.ah.start_bal.iter().any(|sb| sb.seat == p.seat);
+            let visible = Some(p.seat) == viewer || shown.iter().any(|(seat, _)| *seat == p.seat);
+            PlayerView {
+                seat: p.seat,
+                chips: p.chips,
+                is_folded: p.is_folded,
+                is_all_in: p.is_all_in,
+                sitting_out: p.sitting_out,
+                hand: if visible && in_hand { Some(p.hand) } else { None },
+            }
+        }).collect();
+        let my_turn = viewer.is_some() && viewer == Some(self.curr) && self.stage.is_betting();
+        TableView {
+            viewer,
+            stage: self.stage.clone(),
+            button: self.button,
+            curr: self.curr,
+            pot: self.pot,
+            // the flop is dealt even when everyone folds preflop, nobody played those cards
+            flop: if self.saw_flop() { Some(self.flop) } else { None },
+            players,
+            actions: self.ah.actions.clone(),
+            pf: self.ah.pf.clone(),
+            f: self.ah.f.clone(),
+            call_amt: if my_turn { self.get_call_amt(&self.curr) } else { 0 },
+            legal: if my_turn { self.get_available_actions() } else { vec![] },
+        }
+    }
+
+    // hole cards policy shows to every seated player, empty until the hand is at showdown after the flop
+    pub fn shown_hands(&self, policy: &RevealPolicy) -> Vec<(u8, [Card; 4])> {
+        if !matches!(self.stage, Stages::AwaitingSettlement | Stages::Settled) || !self.saw_flop() || !self.hands_dealt() {
+            return vec![];
+        }
+        let contested = self.p.iter().filter(|p| !p.is_folded).count() > 1;
+        self.p.iter().filter(|p| match policy {
+            RevealPolicy::Never => false,
+            RevealPolicy::Showdown => contested && !p.is_folded,
+            RevealPolicy::All => self.ah.start_bal.iter().any(|sb| sb.seat == p.seat),
+        }).map(|p| (p.seat, p.hand)).collect()
+    }
+
+    // true once new_hand has dealt hole cards
+    fn hands_dealt(&self) -> bool {
+        !self.ah.start_bal.is_empty()
+    }
+
+    // what seat is allowed to see, its own hole cards plus others' per policy at showdown
+    pub fn view_for(&self, seat: u8, policy: &RevealPolicy) -> TableView {
+        self.view(Some(seat), policy)
+    }
+
+    // public information only, no hole cards at all
+    pub fn spectator_view(&self) -> TableView {
+        self.view(None, &RevealPolicy::Never)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::player::*;
+
+    fn play_to_showdown() -> Dealer {
+        let mut dealer = Dealer::new(123, vec![
+            Player::new(1, 5),
+            Player::new(2, 5),
+            Player::new(3, 5),
+        ]);
+        dealer.new_hand();
+        dealer.p_action(Action {
+            seat: 1,
+            t: ActionType::Fold,
+            value: 0
+        });
+        dealer.p_action(Action {
+            seat: 2,
+            t: ActionType::Call,
+            value: 0
+        });
+        dealer.p_action(Action {
+            seat: 3,
+            t: ActionType::Check,
+            value: 0
+        });
+        dealer.p_action(Action {
+            seat: 2,
+            t: ActionType::Check,
+            value: 0
+        });
+        dealer
+    }
+
+    #[test]
+    fn test_view_for_mid_hand() {
+        let dealer = play_to_showdown();
+        let view = dealer.view_for(2, &RevealPolicy::All);
+        assert_eq!(view.player(2).unwrap().hand, Some(dealer.p.iter().find(|p| p.seat == 2).unwrap().hand));
+        assert_eq!(view.player(1).unwrap().hand, None);
+        assert_eq!(view.player(3).unwrap().hand, None);
+        assert!(view.flop.is_some());
+        assert!(view.legal.is_empty());
+
+        let view = dealer.view_for(3, &RevealPolicy::All);
+        assert_eq!(view.legal, vec![ActionType::Check, ActionType::BetAI]);
+        assert!(dealer.spectator_view().players.iter().all(|p| p.hand.is_none()));
+    }
+
+    #[test]
+    fn test_view_for_showdown() {
+        let mut dealer = play_to_showdown();
+        dealer.p_action(Action {
+            seat: 3,
+            t: ActionType::Check,
+            value: 0
+        });
+        assert_eq!(dealer.stage, Stages::AwaitingSettlement);
+
+        let view = dealer.view_for(2, &RevealPolicy::Showdown);
+        assert!(view.player(3).unwrap().hand.is_some());
+        // seat 1 folded
+        assert!(view.player(1).unwrap().hand.is_none());
+
+        let view = dealer.view_for(2, &RevealPolicy::Never);
+        assert!(view.player(3).unwrap().hand.is_none());
+        assert!(view.player(2).unwrap().hand.is_some());
+
+        let view = dealer.view_for(2, &RevealPolicy::All);
+        assert!(view.players.iter().all(|p| p.hand.is_some()));
+
+        let shown = dealer.shown_hands(&RevealPolicy::Showdown).iter().map(|(seat, _)| *seat).collect::<Vec<u8>>();
+        assert_eq!(shown, vec![2, 3]);
+
+        assert!(dealer.spectator_view().players.iter().all(|p| p.hand.is_none()));
+    }
+
+    #[test]
+    fn test_view_preflop_fold() {
+        let mut dealer = Dealer::new(123, vec![
+            Player::new(1, 5),
+            Player::new(2, 5),
+            Player::new(3, 5),
+        ]);
+        dealer.new_hand();
+        dealer.p_action(Action { seat: 1, t: ActionType::Fold, value: 0 });
+        dealer.p_action(Action { seat: 2, t: ActionType::Fold, value: 0 });
+        assert_eq!(dealer.stage, Stages::AwaitingSettlement);
+        // the dealer still dealt a flop nobody saw
+        assert_ne!(dealer.flop[0].value, 0);
+        assert!(dealer.view_for(3, &RevealPolicy::All).flop.is_none());
+        assert!(dealer.spectator_view().flop.is_none());
+        assert!(dealer.shown_hands(&RevealPolicy::All).is_empty());
+        dealer.handle_showdown();
+        assert!(dealer.spectator_view().flop.is_none());
+    }
+
+    #[test]
+    fn test_view_before_first_hand() {
+        let dealer = Dealer::new(123, vec![
+            Player::new(1, 5),
+            Player::new(2, 5),
+        ]);
+        let view = dealer.view_for(1, &RevealPolicy::All);
+        assert!(view.players.iter().all(|p| p.hand.is_none()));
+        assert!(view.legal.is_empty());
+    }
+}
@@ -0,0 +1,119 @@
+use wasm_bindgen::prelude::*;
+
+use crate::dealer::*;
+use crate::deck::*;
+use crate::error::*;
+use crate::player::*;
+
+// wasm-bindgen wrapper around Dealer, built with the wasm feature
+//   cargo rustc --release --lib --target wasm32-unknown-unknown --features wasm --crate-type cdylib
+// then run wasm-bindgen over the .wasm, the manifest only builds the rlib
+// everything goes through the try_ functions so bad input comes back to js as an Error
+// instead of a panic, which aborts the whole wasm instance
+// action types are the short codes from ActionType's Display, cards are "As" style strings
+
+fn to_js(e: DealerError) -> JsError {
+    JsError::new(&e.to_string())
+}
+
+#[wasm_bindgen]
+pub struct WasmDealer {
+    dealer: Dealer,
+}
+
+#[wasm_bindgen]
+impl WasmDealer {
+    #[wasm_bindgen(constructor)]
+    pub fn new(seed: u64, seats: Vec<u8>, chips: Vec<u16>) -> Result<WasmDealer, JsError> {
+        if seats.len() != chips.len() {
+            return Err(JsError::new("seats and chips must be the same length"));
+        }
+        let p = seats.iter().zip(chips.iter()).map(|(s, c)| Player::new(*s, *c)).collect::<Vec<Player>>();
+        Ok(WasmDealer { dealer: Dealer::new(seed, p) })
+    }
+
+    #[wasm_bindgen(js_name = newHand)]
+    pub fn new_hand(&mut self) -> Result<(), JsError> {
+        self.dealer.try_new_hand().map_err(to_js)
+    }
+
+    // checks an action without applying it, for validating input as the user types
+    pub fn validate(&self, seat: u8, t: &str, value: u16) -> Result<(), JsError> {
+        let t = t.parse::<ActionType>().map_err(|e| JsError::new(&e))?;
+        self.dealer.check_action(&Action { seat, t, value }).map_err(to_js)
+    }
+
+    pub fn act(&mut self, seat: u8, t: &str, value: u16) -> Result<(), JsError> {
+        let t = t.parse::<ActionType>().map_err(|e| JsError::new(&e))?;
+        self.dealer.try_p_action(Action { seat, t, value }).map_err(to_js)
+    }
+
+    pub fn showdown(&mut self) -> Result<(), JsError> {
+        self.dealer.try_handle_showdown().map(|_| ()).map_err(to_js)
+    }
+
+    // empty once the hand is over
+    #[wasm_bindgen(js_name = legalActions)]
+    pub fn legal_actions(&self) -> Vec<String> {
+        if !self.dealer.stage.is_betting() {
+            return vec![];
+        }
+        self.dealer.get_available_actions().iter().map(|t| t.to_string()).collect()
+    }
+
+    // [min, max] for a bet or raise by the current player
+    #[wasm_bindgen(js_name = betRange)]
+    pub fn bet_range(&self) -> Vec<u16> {
+        if !self.dealer.stage.is_betting() {
+            return vec![0, 0];
+        }
+        let (min, max) = self.dealer.get_bet_range();
+        vec![min, max]
+    }
+
+    #[wasm_bindgen(js_name = callAmt)]
+    pub fn call_amt(&self, seat: u8) -> u16 {
+        self.dealer.get_call_amt(&seat)
+    }
+
+    pub fn chips(&self, seat: u8) -> Option<u16> {
+        self.dealer.p.iter().find(|p| p.seat == seat).map(|p| p.chips)
+    }
+
+    pub fn hand(&self, seat: u8) -> Vec<String> {
+        match self.dealer.p.iter().find(|p| p.seat == seat) {
+            Some(p) => p.hand.iter().map(card_to_string).collect(),
+            None => vec![],
+        }
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn stage(&self) -> String {
+        format!("{:?}", self.dealer.stage)
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn curr(&self) -> u8 {
+        self.dealer.curr
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn button(&self) -> u8 {
+        self.dealer.button
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn pot(&self) -> u16 {
+        self.dealer.pot
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn flop(&self) -> Vec<String> {
+        self.dealer.flop.iter().map(card_to_string).collect()
+    }
+
+    #[wasm_bindgen(js_name = handHistory)]
+    pub fn hand_history(&self) -> String {
+        self.dealer.hand_history()
+    }
+}